@@ -0,0 +1,337 @@
+use crate::{config::Guesses, question::APOSTROPHES};
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+/// Lowercase `text` without stress marks and other diacritics, with a single kind of apostrophe.
+/// Й and ї keep their marks, as they are letters of their own rather than accented и and і.
+pub fn normalize(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut base = ' ';
+    for c in text.nfd() {
+        if !is_combining_mark(c) {
+            base = c;
+            plain.push(if APOSTROPHES.contains(&c) { '\'' } else { c });
+        } else if matches!((base, c), ('и' | 'И', '\u{306}') | ('і' | 'І', '\u{308}')) {
+            plain.push(c);
+        }
+    }
+    plain.nfc().collect::<String>().to_lowercase()
+}
+
+/// Normalized words of the `text`, apostrophes are a part of words and hyphens are not
+fn words(text: &str) -> Vec<String> {
+    normalize(text)
+        .split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .map(|word| word.trim_matches('\''))
+        .filter(|word| !word.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Whether message `text` has the `answer` among its words, ignoring case and diacritics.
+/// Answers of several words may also be written as one.
+pub fn matches(text: &str, answer: &str) -> bool {
+    let text = words(text);
+    match words(answer).as_slice() {
+        [] => false,
+        [word] => text.contains(word),
+        answer => {
+            text.windows(answer.len()).any(|words| words == answer)
+                || text.contains(&answer.concat())
+        }
+    }
+}
+
+/// Ukrainian letters on the keys of the English layout, shifted keys are left out
+/// as they are more often quotes and punctuation than capital letters
+const LAYOUT: [(char, char); 35] = [
+    ('q', 'й'),
+    ('w', 'ц'),
+    ('e', 'у'),
+    ('r', 'к'),
+    ('t', 'е'),
+    ('y', 'н'),
+    ('u', 'г'),
+    ('i', 'ш'),
+    ('o', 'щ'),
+    ('p', 'з'),
+    ('[', 'х'),
+    (']', 'ї'),
+    ('a', 'ф'),
+    ('s', 'і'),
+    ('d', 'в'),
+    ('f', 'а'),
+    ('g', 'п'),
+    ('h', 'р'),
+    ('j', 'о'),
+    ('k', 'л'),
+    ('l', 'д'),
+    (';', 'ж'),
+    ('\'', 'є'),
+    ('z', 'я'),
+    ('x', 'ч'),
+    ('c', 'с'),
+    ('v', 'м'),
+    ('b', 'и'),
+    ('n', 'т'),
+    ('m', 'ь'),
+    (',', 'б'),
+    ('.', 'ю'),
+    ('/', '.'),
+    ('`', '\''),
+    ('\\', 'ґ'),
+];
+
+/// `text` typed in the English layout as if it was typed in the Ukrainian one,
+/// `text` without Latin letters is left as it is
+fn from_layout(text: &str) -> String {
+    if !text.chars().any(|c| c.is_ascii_alphabetic()) {
+        return text.to_string();
+    }
+    text.chars()
+        .map(|c| {
+            let lower = c.to_ascii_lowercase();
+            match LAYOUT.iter().find(|(key, _)| *key == lower) {
+                Some((_, letter)) if c.is_ascii_uppercase() => {
+                    letter.to_uppercase().next().unwrap_or(*letter)
+                }
+                Some((_, letter)) => *letter,
+                None => c,
+            }
+        })
+        .collect()
+}
+
+/// Normalized `text` in Latin letters by the Ukrainian national transliteration (KMU 2010)
+fn transliterate(text: &str) -> String {
+    let mut latin = String::with_capacity(text.len());
+    let mut previous = ' ';
+    for c in normalize(text).chars() {
+        // є, ї, й, ю and я are spelled differently at the start of a word
+        let initial = !previous.is_alphabetic() && previous != '\'';
+        let letters = match c {
+            'а' => "a",
+            'б' => "b",
+            'в' => "v",
+            // зг is spelled zgh to tell it from ж
+            'г' if previous == 'з' => "gh",
+            'г' => "h",
+            'ґ' => "g",
+            'д' => "d",
+            'е' => "e",
+            'є' if initial => "ye",
+            'є' => "ie",
+            'ж' => "zh",
+            'з' => "z",
+            'и' => "y",
+            'і' => "i",
+            'ї' if initial => "yi",
+            'ї' => "i",
+            'й' if initial => "y",
+            'й' => "i",
+            'к' => "k",
+            'л' => "l",
+            'м' => "m",
+            'н' => "n",
+            'о' => "o",
+            'п' => "p",
+            'р' => "r",
+            'с' => "s",
+            'т' => "t",
+            'у' => "u",
+            'ф' => "f",
+            'х' => "kh",
+            'ц' => "ts",
+            'ч' => "ch",
+            'ш' => "sh",
+            'щ' => "shch",
+            'ю' if initial => "yu",
+            'ю' => "iu",
+            'я' if initial => "ya",
+            'я' => "ia",
+            'ь' | '\'' => "",
+            _ => {
+                latin.push(c);
+                previous = c;
+                continue;
+            }
+        };
+        latin.push_str(letters);
+        previous = c;
+    }
+    latin
+}
+
+/// How close a message comes to the answer
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Guess {
+    Correct,
+    /// Off by no more typos than the answer length allows
+    Close,
+    Wrong,
+}
+
+/// Typos allowed in an answer of `len` letters, short words have too many neighbours to allow any
+fn typos(len: usize) -> usize {
+    match len {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    }
+}
+
+/// Letters inserted, deleted, replaced or swapped with a neighbour to turn `a` into `b`
+/// (optimal string alignment distance)
+fn distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    // rows for the two previous letters of `a` and the current one
+    let mut before: Vec<usize> = vec![0; b.len() + 1];
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        let mut current = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            current[j] = (previous[j] + 1)
+                .min(current[j - 1] + 1)
+                .min(previous[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                current[j] = current[j].min(before[j - 2] + 1);
+            }
+        }
+        before = std::mem::replace(&mut previous, current);
+    }
+    previous[b.len()]
+}
+
+/// Whether message `text` has the `answer`, has it with a few typos or does not have it.
+/// Ukrainian answers may also be typed in the English layout or in transliteration.
+pub fn guess(text: &str, answer: &str) -> Guess {
+    variants(text, answer)
+        .iter()
+        .map(|(text, answer)| compare(text, answer))
+        .min()
+        .unwrap_or(Guess::Wrong)
+}
+
+/// Message `text` and `answer` as they are, with the text out of the English layout
+/// and with the answer in transliteration
+fn variants(text: &str, answer: &str) -> [(String, String); 3] {
+    [
+        (text.to_string(), answer.to_string()),
+        (from_layout(text), answer.to_string()),
+        (text.to_string(), transliterate(answer)),
+    ]
+}
+
+/// Whether message `text` is a guess at the `answer` by the `guesses` rule rather than chat
+pub fn is_guess(text: &str, answer: &str, guesses: Guesses, prefix: &str) -> bool {
+    match guesses {
+        Guesses::All => true,
+        Guesses::Words => text.split_whitespace().count() <= words(answer).len().max(1),
+        Guesses::Length => variants(text, answer).iter().any(|(text, answer)| {
+            let letters = |text| words(text).concat().chars().count();
+            let length = letters(answer);
+            letters(text).abs_diff(length) <= typos(length)
+        }),
+        Guesses::Prefix => text.trim_start().starts_with(prefix),
+    }
+}
+
+fn compare(text: &str, answer: &str) -> Guess {
+    if matches(text, answer) {
+        return Guess::Correct;
+    }
+    let answer = words(answer);
+    if answer.is_empty() {
+        return Guess::Wrong;
+    }
+    let text = words(text);
+    let joined = answer.join(" ");
+    let concat = answer.concat();
+    let allowed = typos(concat.chars().count());
+    let close = text
+        .windows(answer.len())
+        .any(|words| distance(&words.join(" "), &joined) <= allowed)
+        || text.iter().any(|word| distance(word, &concat) <= allowed);
+    if close {
+        Guess::Close
+    } else {
+        Guess::Wrong
+    }
+}
+
+#[test]
+fn normalize_test() {
+    assert_eq!(normalize("Заванта\u{301}жувати"), "завантажувати");
+    assert_eq!(normalize("сі́м"), "сім");
+    assert_eq!(normalize("Їжак й ЙОГО"), "їжак й його");
+    assert_eq!(normalize("м’ята мʼята м'ята"), "м'ята м'ята м'ята");
+    assert_eq!(normalize("Café"), "cafe");
+}
+
+#[test]
+fn matches_test() {
+    assert!(matches("Це завантажувати!", "заванта\u{301}жувати"));
+    assert!(matches("м’ята", "мʼята"));
+    assert!(matches("ПІВ ЯБЛУКА", "пів яблука"));
+    assert!(matches("це півяблука", "пів-яблука"));
+    assert!(matches("пів-яблука?", "пів яблука"));
+    assert!(!matches("яблука пів", "пів яблука"));
+    assert!(!matches("іжак", "їжак"));
+    assert!(!matches("китаєць", "кит"));
+    assert!(!matches("абвгкитдеє", "кит"));
+    assert!(matches("'кит', кажу", "кит"));
+    assert!(!matches("", ""));
+}
+
+#[test]
+fn guess_test() {
+    assert_eq!(distance("кіт", "кит"), 1);
+    assert_eq!(distance("яблкуа", "яблука"), 1);
+    assert_eq!(distance("", "кит"), 3);
+    assert_eq!(distance("завнтажувти", "завантажувати"), 2);
+    assert_eq!(guess("кит!", "кит"), Guess::Correct);
+    assert_eq!(guess("кіт", "кит"), Guess::Wrong);
+    assert_eq!(guess("це яблкуо", "яблуко"), Guess::Close);
+    assert_eq!(guess("це яблко", "яблуко"), Guess::Close);
+    assert_eq!(guess("це ябко", "яблуко"), Guess::Wrong);
+    assert_eq!(guess("завнтажувти", "завантажувати"), Guess::Close);
+    assert_eq!(guess("пів ябука", "пів яблука"), Guess::Close);
+    assert_eq!(guess("півябука", "пів яблука"), Guess::Close);
+    assert_eq!(guess("", ""), Guess::Wrong);
+}
+
+#[test]
+fn mistyped_test() {
+    assert_eq!(from_layout("pfdfynf;edfnb"), "завантажувати");
+    assert_eq!(from_layout("V`znf ujhs["), "М'ята горіх");
+    assert_eq!(from_layout("м'ята."), "м'ята.");
+    assert_eq!(transliterate("Заванта\u{301}жувати"), "zavantazhuvaty");
+    assert_eq!(transliterate("Їжак йде в м’яту"), "yizhak yde v miatu");
+    assert_eq!(
+        transliterate("Згода, щастя, Юрій"),
+        "zghoda, shchastia, yurii"
+    );
+    assert_eq!(guess("pfdfynf;edfnb", "завантажувати"), Guess::Correct);
+    assert_eq!(guess("це zavantazhuvaty", "завантажувати"), Guess::Correct);
+    assert_eq!(guess("zavantazhuvati", "завантажувати"), Guess::Close);
+    assert_eq!(guess("rbn", "кит"), Guess::Correct);
+    assert_eq!(guess("kyt", "кит"), Guess::Correct);
+    assert_eq!(guess("kit", "кит"), Guess::Wrong);
+    assert_eq!(guess("cat", "cat"), Guess::Correct);
+}
+
+#[test]
+fn is_guess_test() {
+    let guess = |text, guesses| is_guess(text, "яблуко", guesses, "=");
+    assert!(guess("ну привіт усім", Guesses::All));
+    assert!(guess(" груша ", Guesses::Words));
+    assert!(!guess("ну привіт", Guesses::Words));
+    assert!(is_guess("пів груші", "пів яблука", Guesses::Words, "="));
+    assert!(guess("грушка", Guesses::Length));
+    assert!(guess("груша", Guesses::Length));
+    assert!(guess("zabluko", Guesses::Length));
+    assert!(!guess("ну привіт усім", Guesses::Length));
+    assert!(guess("=груша", Guesses::Prefix));
+    assert!(!guess("груша", Guesses::Prefix));
+}
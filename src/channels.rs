@@ -0,0 +1,110 @@
+use crate::{question::Lang, transport::ChannelId};
+use sqlite::{Connection, Result};
+use std::collections::HashMap;
+
+/// Whether the game is played in a channel, set with `start`, `stop`, `pause` and `resume`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameState {
+    Running,
+    /// The current question waits for the game to be resumed
+    Paused,
+    /// There is no current question
+    Stopped,
+}
+
+impl GameState {
+    fn from_code(code: &str) -> Option<GameState> {
+        match code {
+            "running" => Some(GameState::Running),
+            "paused" => Some(GameState::Paused),
+            "stopped" => Some(GameState::Stopped),
+            _ => None,
+        }
+    }
+
+    fn code(&self) -> &'static str {
+        match self {
+            GameState::Running => "running",
+            GameState::Paused => "paused",
+            GameState::Stopped => "stopped",
+        }
+    }
+}
+
+/// Game states of the channels where it was ever started, stopped or paused
+pub fn get_states(db: &Connection) -> Result<HashMap<ChannelId, GameState>> {
+    let mut states = HashMap::new();
+    for row in db.prepare("SELECT * FROM games")? {
+        let row = row?;
+        if let Some(state) = GameState::from_code(row.try_read::<&str, _>("state")?) {
+            states.insert(ChannelId(row.try_read::<i64, _>("channel")? as u64), state);
+        }
+    }
+    Ok(states)
+}
+
+pub fn set_state(db: &Connection, channel: ChannelId, state: GameState) -> Result<()> {
+    let mut statement =
+        db.prepare("INSERT OR REPLACE INTO games (channel, state) VALUES (:channel, :state)")?;
+    statement.bind((":channel", channel.0 as i64))?;
+    statement.bind((":state", state.code()))?;
+    statement.next()?;
+    Ok(())
+}
+
+/// Languages channels were bound to with `bind` command
+pub fn get_bindings(db: &Connection) -> Result<HashMap<ChannelId, Lang>> {
+    let mut bindings = HashMap::new();
+    for row in db.prepare("SELECT * FROM channels")? {
+        let row = row?;
+        if let Some(lang) = Lang::from_code(row.try_read::<&str, _>("lang")?) {
+            bindings.insert(ChannelId(row.try_read::<i64, _>("channel")? as u64), lang);
+        }
+    }
+    Ok(bindings)
+}
+
+pub fn bind(db: &Connection, channel: ChannelId, lang: Lang) -> Result<()> {
+    let mut statement =
+        db.prepare("INSERT OR REPLACE INTO channels (channel, lang) VALUES (:channel, :lang)")?;
+    statement.bind((":channel", channel.0 as i64))?;
+    statement.bind((":lang", lang.code()))?;
+    statement.next()?;
+    Ok(())
+}
+
+pub fn unbind(db: &Connection, channel: ChannelId) -> Result<()> {
+    let mut statement = db.prepare("DELETE FROM channels WHERE channel == :channel")?;
+    statement.bind((":channel", channel.0 as i64))?;
+    statement.next()?;
+    Ok(())
+}
+
+#[test]
+fn bindings_test() {
+    let db = sqlite::open(":memory:").unwrap();
+    crate::migrations::migrate(&db).unwrap();
+    bind(&db, ChannelId(u64::MAX), Lang::Uk).unwrap();
+    bind(&db, ChannelId(2), Lang::Uk).unwrap();
+    bind(&db, ChannelId(2), Lang::En).unwrap();
+    unbind(&db, ChannelId(3)).unwrap();
+    let bindings = get_bindings(&db).unwrap();
+    assert_eq!(bindings.len(), 2);
+    assert_eq!(bindings[&ChannelId(u64::MAX)], Lang::Uk);
+    assert_eq!(bindings[&ChannelId(2)], Lang::En);
+    unbind(&db, ChannelId(2)).unwrap();
+    assert!(!get_bindings(&db).unwrap().contains_key(&ChannelId(2)));
+}
+
+#[test]
+fn states_test() {
+    let db = sqlite::open(":memory:").unwrap();
+    crate::migrations::migrate(&db).unwrap();
+    set_state(&db, ChannelId(1), GameState::Paused).unwrap();
+    set_state(&db, ChannelId(2), GameState::Stopped).unwrap();
+    set_state(&db, ChannelId(2), GameState::Running).unwrap();
+    let states = get_states(&db).unwrap();
+    assert_eq!(states.len(), 2);
+    assert_eq!(states[&ChannelId(1)], GameState::Paused);
+    assert_eq!(states[&ChannelId(2)], GameState::Running);
+}
@@ -0,0 +1,297 @@
+use crate::question::Lang;
+
+/// What a command does, the engine handles each one
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Command {
+    Help,
+    Next,
+    Question,
+    Hint,
+    Score,
+    Top,
+    Bind,
+    Unbind,
+    Start,
+    Stop,
+    Pause,
+    Resume,
+}
+
+/// Who is allowed to use a command
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Permission {
+    Everyone,
+    /// Users listed in `admins` of the config
+    Admin,
+}
+
+/// Names and description of a command in one language
+pub struct Names {
+    /// The first one is the main name, the rest are aliases
+    pub names: &'static [&'static str],
+    /// Shown in help, `{p}` is replaced with the prefix
+    pub description: &'static str,
+}
+
+/// A command available in the game channels
+pub struct CommandSpec {
+    pub command: Command,
+    pub permission: Permission,
+    /// Whether the rest of the message after the name is an argument, e.g. `!binden`
+    pub argument: bool,
+    pub uk: Names,
+    pub en: Names,
+}
+
+impl CommandSpec {
+    /// Names of the command in a `lang` channel
+    pub fn names(&self, lang: Lang) -> &'static [&'static str] {
+        match lang {
+            Lang::Uk => self.uk.names,
+            Lang::En | Lang::Uknown => self.en.names,
+        }
+    }
+
+    fn description(&self, lang: Lang) -> &'static str {
+        match lang {
+            Lang::Uk => self.uk.description,
+            Lang::En | Lang::Uknown => self.en.description,
+        }
+    }
+
+    /// Argument of the command if `text` calls it by one of the `lang` names
+    fn matches<'t>(&self, lang: Lang, text: &'t str) -> Option<&'t str> {
+        self.names(lang)
+            .iter()
+            .find_map(|name| match text.strip_prefix(name) {
+                Some("") => Some(""),
+                Some(arg) if self.argument => Some(arg),
+                _ => None,
+            })
+    }
+}
+
+/// All the commands, in the order they are listed in help
+pub const COMMANDS: &[CommandSpec] = &[
+    CommandSpec {
+        command: Command::Help,
+        permission: Permission::Everyone,
+        argument: false,
+        uk: Names {
+            names: &["?", "help"],
+            description: "інформація і команди",
+        },
+        en: Names {
+            names: &["?", "help"],
+            description: "info and commands",
+        },
+    },
+    CommandSpec {
+        command: Command::Next,
+        permission: Permission::Everyone,
+        argument: false,
+        uk: Names {
+            names: &["next", "далі", "відповідь"],
+            description: "відповідь на поточне питання і нове питання",
+        },
+        en: Names {
+            names: &["next", "answer"],
+            description: "shows answer to current question and provides a new one",
+        },
+    },
+    CommandSpec {
+        command: Command::Question,
+        permission: Permission::Everyone,
+        argument: false,
+        uk: Names {
+            names: &["q", "питання", "п"],
+            description: "повторити поточне питання",
+        },
+        en: Names {
+            names: &["q", "question"],
+            description: "repeat current question",
+        },
+    },
+    CommandSpec {
+        command: Command::Hint,
+        permission: Permission::Everyone,
+        argument: false,
+        uk: Names {
+            names: &["підказка", "хінт"],
+            description: "наступна підказка: довжина слова, перша і остання літери, \
+                          ще літери, синонім; як і реакція ❓ до питання",
+        },
+        en: Names {
+            names: &["hint"],
+            description: "next hint: length of the word, first and last letters, more letters; \
+                          same as ❓ reaction under the question",
+        },
+    },
+    CommandSpec {
+        command: Command::Top,
+        permission: Permission::Everyone,
+        argument: false,
+        uk: Names {
+            names: &["топ"],
+            description: "відобразити топ 10 гравців з найвищим рейтингом",
+        },
+        en: Names {
+            names: &["top"],
+            description: "top 10 score standings",
+        },
+    },
+    CommandSpec {
+        command: Command::Score,
+        permission: Permission::Everyone,
+        argument: false,
+        uk: Names {
+            names: &["рейтинг"],
+            description: "відобразити Ваш рейтинг",
+        },
+        en: Names {
+            names: &["score"],
+            description: "display Your score",
+        },
+    },
+    CommandSpec {
+        command: Command::Bind,
+        permission: Permission::Admin,
+        argument: true,
+        uk: Names {
+            names: &["bind"],
+            description: "грати в каналі мовою з кодом, напр. {p}bind en",
+        },
+        en: Names {
+            names: &["bind"],
+            description: "play in the channel in language with the code, e.g. {p}bind uk",
+        },
+    },
+    CommandSpec {
+        command: Command::Unbind,
+        permission: Permission::Admin,
+        argument: false,
+        uk: Names {
+            names: &["unbind"],
+            description: "повернути каналу мову з налаштувань",
+        },
+        en: Names {
+            names: &["unbind"],
+            description: "return the channel to its configured language",
+        },
+    },
+    CommandSpec {
+        command: Command::Start,
+        permission: Permission::Admin,
+        argument: false,
+        uk: Names {
+            names: &["start", "старт"],
+            description: "почати гру в каналі з нового питання",
+        },
+        en: Names {
+            names: &["start"],
+            description: "start the game in the channel with a new question",
+        },
+    },
+    CommandSpec {
+        command: Command::Stop,
+        permission: Permission::Admin,
+        argument: false,
+        uk: Names {
+            names: &["stop", "стоп"],
+            description: "зупинити гру в каналі",
+        },
+        en: Names {
+            names: &["stop"],
+            description: "stop the game in the channel",
+        },
+    },
+    CommandSpec {
+        command: Command::Pause,
+        permission: Permission::Admin,
+        argument: false,
+        uk: Names {
+            names: &["pause", "пауза"],
+            description: "призупинити гру, питання залишається до {p}resume",
+        },
+        en: Names {
+            names: &["pause"],
+            description: "pause the game, the question stays until {p}resume",
+        },
+    },
+    CommandSpec {
+        command: Command::Resume,
+        permission: Permission::Admin,
+        argument: false,
+        uk: Names {
+            names: &["resume", "продовжити"],
+            description: "продовжити гру з того ж питання",
+        },
+        en: Names {
+            names: &["resume"],
+            description: "resume the game with the same question",
+        },
+    },
+];
+
+/// Command called by normalized message `text` with the prefix stripped in a `lang` channel,
+/// with its argument. Channels without a language understand names in any language.
+pub fn find(text: &str, lang: Lang) -> Option<(&'static CommandSpec, &str)> {
+    COMMANDS.iter().find_map(|spec| {
+        let arg = match lang {
+            Lang::Uknown => spec
+                .matches(Lang::Uk, text)
+                .or_else(|| spec.matches(Lang::En, text)),
+            _ => spec.matches(lang, text),
+        }?;
+        Some((spec, arg))
+    })
+}
+
+/// Help lines for the commands available to users with `permission` in a `lang` channel
+pub fn help(lang: Lang, prefix: &str, permission: Permission) -> String {
+    COMMANDS
+        .iter()
+        .filter(|spec| spec.permission <= permission)
+        .map(|spec| {
+            let names: Vec<String> = spec
+                .names(lang)
+                .iter()
+                .map(|name| format!("**{}{}**", prefix, name))
+                .collect();
+            format!(
+                "{} - {};\n",
+                names.join(" | "),
+                spec.description(lang).replace("{p}", prefix)
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn find_test() {
+    let command = |text, lang| find(text, lang).map(|(spec, arg)| (spec.command, arg));
+    assert_eq!(command("далі", Lang::Uk), Some((Command::Next, "")));
+    assert_eq!(command("далі", Lang::En), None);
+    assert_eq!(command("answer", Lang::En), Some((Command::Next, "")));
+    assert_eq!(command("nexty", Lang::En), None);
+    assert_eq!(command("binden", Lang::Uknown), Some((Command::Bind, "en")));
+    assert_eq!(command("unbind", Lang::Uk), Some((Command::Unbind, "")));
+    assert_eq!(command("п", Lang::Uknown), Some((Command::Question, "")));
+    assert_eq!(command("пауза", Lang::Uk), Some((Command::Pause, "")));
+    assert_eq!(command("startnow", Lang::En), None);
+}
+
+#[test]
+fn help_test() {
+    for lang in [Lang::Uk, Lang::En] {
+        let help = help(lang, "!", Permission::Everyone);
+        for spec in COMMANDS {
+            for name in spec.names(lang) {
+                let listed = help.contains(&format!("**!{}**", name));
+                assert_eq!(listed, spec.permission == Permission::Everyone, "{}", name);
+            }
+        }
+    }
+    assert!(help(Lang::En, ".", Permission::Admin)
+        .contains("**.bind** - play in the channel in language with the code, e.g. .bind uk;"));
+}
@@ -0,0 +1,599 @@
+use crate::{
+    commands::Permission,
+    question::Lang,
+    transport::{ChannelId, UserId},
+};
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    env,
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// Prefix of environment variables overriding config values, e.g. `GAMEBOT_PREFIX`
+pub const ENV_PREFIX: &str = "GAMEBOT_";
+/// Config file read when no `--config` argument or `GAMEBOT_CONFIG` variable is given
+pub const DEFAULT_CONFIG: &str = "gamebot.toml";
+
+/// Bot settings, read from TOML file:
+/// ```toml
+/// uk_dictionary = "db/synsets_ua.db"
+/// en_dictionary = "db/synsets_en.db"
+/// state_db = "db/state.db" # scores, channels and games
+/// score_db = "db/synsets_ua.db" # scores of older versions, imported into a new state_db
+/// min_pause = 60 # seconds between hints and next questions
+/// hint_after = 120 # seconds before the bot hints by itself, 0 to never
+/// reveal_after = 120 # seconds after the hint before the bot reveals the answer, 0 to never
+/// pause_after = 0 # seconds without guesses before the bot pauses until somebody writes, 0 to never
+/// hint_cost = "20%" # taken off the reward by each hint, in percent or points
+/// prefix = "!"
+/// admins = [241977946434076672] # users allowed to `bind` channels
+/// match_channel_names = false # guess language of unbound channels by "uk"/"en" part of the name
+/// typos = "almost" # answers with a typo are "off", "almost" or "accept"-ed for one hint less
+/// guesses = "all" # messages reacted to when wrong: "all", "words", "length" or "prefix"-ed ones
+/// guess_prefix = "=" # starts a guess in "prefix" mode, e.g. `=кит`
+/// react_wrong = true # react to wrong guesses with ➖
+///
+/// [channels]
+/// 1165158145035190363 = "uk"
+///
+/// [channel_typos]
+/// 1165158145035190363 = "accept"
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub uk_dictionary: PathBuf,
+    pub en_dictionary: PathBuf,
+    pub state_db: PathBuf,
+    pub score_db: PathBuf,
+    pub min_pause: u64,
+    pub hint_after: u64,
+    pub reveal_after: u64,
+    pub pause_after: u64,
+    pub hint_cost: HintCost,
+    pub prefix: String,
+    pub admins: Vec<u64>,
+    pub match_channel_names: bool,
+    pub typos: Typos,
+    pub guesses: Guesses,
+    pub guess_prefix: String,
+    pub react_wrong: bool,
+    // channel id to language code
+    channels: HashMap<String, String>,
+    #[serde(skip)]
+    languages: HashMap<ChannelId, Lang>,
+    // channel id to what to do with typos there
+    channel_typos: HashMap<String, Typos>,
+    #[serde(skip)]
+    typos_by_channel: HashMap<ChannelId, Typos>,
+}
+
+/// What to do with answers which are off by a typo or two
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum Typos {
+    /// Treat them as wrong
+    Off,
+    /// React to them with 🤏
+    Almost,
+    /// Count them as correct, for the reward of one more hint
+    Accept,
+}
+
+impl std::str::FromStr for Typos {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim() {
+            "off" => Ok(Typos::Off),
+            "almost" => Ok(Typos::Almost),
+            "accept" => Ok(Typos::Accept),
+            _ => Err(format!(
+                "{value:?} is not one of \"off\", \"almost\" or \"accept\""
+            )),
+        }
+    }
+}
+
+impl TryFrom<String> for Typos {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+/// Which messages that do not have the answer are wrong guesses rather than chat
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum Guesses {
+    /// Every message
+    All,
+    /// Messages of no more words than the answer, a single word for most answers
+    Words,
+    /// Messages of as many letters as the answer, give or take allowed typos
+    Length,
+    /// Messages starting with `guess_prefix`
+    Prefix,
+}
+
+impl std::str::FromStr for Guesses {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim() {
+            "all" => Ok(Guesses::All),
+            "words" => Ok(Guesses::Words),
+            "length" => Ok(Guesses::Length),
+            "prefix" => Ok(Guesses::Prefix),
+            _ => Err(format!(
+                "{value:?} is not one of \"all\", \"words\", \"length\" or \"prefix\""
+            )),
+        }
+    }
+}
+
+impl TryFrom<String> for Guesses {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+/// How much each hint takes off the reward for the answer
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum HintCost {
+    /// Percent of the full reward, written as `"20%"`
+    Percent(u8),
+    /// Fixed number of points, written as `"1"`
+    Points(i64),
+}
+
+impl HintCost {
+    /// Reward for the answer worth `score` after `hints`, never below zero
+    pub fn reward(&self, score: i64, hints: usize) -> i64 {
+        let hints = hints as i64;
+        let reward = match self {
+            HintCost::Percent(percent) => score * (100 - *percent as i64 * hints).max(0) / 100,
+            HintCost::Points(points) => score - points * hints,
+        };
+        reward.max(0)
+    }
+}
+
+impl std::str::FromStr for HintCost {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        match value.strip_suffix('%') {
+            Some(percent) => match percent.trim().parse() {
+                Ok(percent) if percent <= 100 => Ok(HintCost::Percent(percent)),
+                _ => Err(format!("{value:?} is not a percent from 0% to 100%")),
+            },
+            None => match value.parse() {
+                Ok(points) if points >= 0 => Ok(HintCost::Points(points)),
+                _ => Err(format!("{value:?} is not a number of points or a percent")),
+            },
+        }
+    }
+}
+
+impl TryFrom<String> for HintCost {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Read(PathBuf, std::io::Error),
+    Parse(PathBuf, toml::de::Error),
+    UnknownKey(String),
+    Invalid(&'static str, String),
+    MissingDictionary(PathBuf),
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Read(path, e) => write!(f, "can't read {}: {}", path.display(), e),
+            ConfigError::Parse(path, e) => write!(f, "can't parse {}: {}", path.display(), e),
+            ConfigError::UnknownKey(key) => write!(f, "unknown config key `{}`", key),
+            ConfigError::Invalid(key, value) => write!(f, "invalid `{}` value: {}", key, value),
+            ConfigError::MissingDictionary(path) => {
+                write!(f, "dictionary {} does not exist", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Default for Config {
+    fn default() -> Self {
+        let base = PathBuf::from(
+            env::var("CARGO_MANIFEST_DIR").unwrap_or(
+                env::current_dir()
+                    .unwrap()
+                    .into_os_string()
+                    .into_string()
+                    .unwrap_or(".".into()),
+            ),
+        );
+        Config {
+            uk_dictionary: base.join("db/synsets_ua.db"),
+            en_dictionary: base.join("db/synsets_en.db"),
+            state_db: base.join("db/state.db"),
+            score_db: base.join("db/synsets_ua.db"),
+            min_pause: 60,
+            hint_after: 120,
+            reveal_after: 120,
+            pause_after: 0,
+            hint_cost: HintCost::Percent(20),
+            prefix: "!".into(),
+            admins: vec![],
+            match_channel_names: false,
+            typos: Typos::Almost,
+            guesses: Guesses::All,
+            guess_prefix: "=".to_string(),
+            react_wrong: true,
+            channels: HashMap::new(),
+            languages: HashMap::new(),
+            channel_typos: HashMap::new(),
+            typos_by_channel: HashMap::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Reads config from the file given with `--config` argument or `GAMEBOT_CONFIG` variable,
+    /// falling back to `gamebot.toml` if it exists, then applies environment and command line
+    /// overrides and validates the result
+    pub fn load(args: &[String]) -> Result<Config, ConfigError> {
+        let explicit = args
+            .windows(2)
+            .find(|w| w[0] == "--config")
+            .map(|w| PathBuf::from(&w[1]))
+            .or_else(|| {
+                env::var(format!("{ENV_PREFIX}CONFIG"))
+                    .ok()
+                    .map(PathBuf::from)
+            });
+        let mut config = match explicit {
+            Some(path) => Config::from_file(&path)?,
+            None if Path::new(DEFAULT_CONFIG).exists() => Config::from_file(DEFAULT_CONFIG)?,
+            None => Config::default(),
+        };
+        config.apply_env()?;
+        config.apply_args(args)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Config, ConfigError> {
+        let path = path.as_ref();
+        let text =
+            fs::read_to_string(path).map_err(|e| ConfigError::Read(path.to_path_buf(), e))?;
+        Config::parse(&text).map_err(|e| ConfigError::Parse(path.to_path_buf(), e))
+    }
+
+    pub fn parse(text: &str) -> Result<Config, toml::de::Error> {
+        toml::from_str(text)
+    }
+
+    /// Overrides a single value by its config `key`
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), ConfigError> {
+        match key {
+            "uk_dictionary" => self.uk_dictionary = value.into(),
+            "en_dictionary" => self.en_dictionary = value.into(),
+            "state_db" => self.state_db = value.into(),
+            "score_db" => self.score_db = value.into(),
+            "min_pause" => self.min_pause = parse("min_pause", value)?,
+            "hint_after" => self.hint_after = parse("hint_after", value)?,
+            "reveal_after" => self.reveal_after = parse("reveal_after", value)?,
+            "pause_after" => self.pause_after = parse("pause_after", value)?,
+            "hint_cost" => {
+                self.hint_cost = value
+                    .parse()
+                    .map_err(|e| ConfigError::Invalid("hint_cost", e))?
+            }
+            "prefix" => self.prefix = value.into(),
+            "admins" => {
+                self.admins = value
+                    .split(',')
+                    .filter(|id| !id.trim().is_empty())
+                    .map(|id| parse("admins", id))
+                    .collect::<Result<_, _>>()?
+            }
+            "match_channel_names" => {
+                self.match_channel_names = parse_bool("match_channel_names", value)?
+            }
+            "typos" => {
+                self.typos = value
+                    .parse()
+                    .map_err(|e| ConfigError::Invalid("typos", e))?
+            }
+            "guesses" => {
+                self.guesses = value
+                    .parse()
+                    .map_err(|e| ConfigError::Invalid("guesses", e))?
+            }
+            "guess_prefix" => self.guess_prefix = value.into(),
+            "react_wrong" => self.react_wrong = parse_bool("react_wrong", value)?,
+            _ => return Err(ConfigError::UnknownKey(key.to_string())),
+        }
+        Ok(())
+    }
+
+    /// Applies `GAMEBOT_<KEY>` environment variables
+    fn apply_env(&mut self) -> Result<(), ConfigError> {
+        for key in [
+            "uk_dictionary",
+            "en_dictionary",
+            "state_db",
+            "score_db",
+            "min_pause",
+            "hint_after",
+            "reveal_after",
+            "pause_after",
+            "hint_cost",
+            "prefix",
+            "admins",
+            "match_channel_names",
+            "typos",
+            "guesses",
+            "guess_prefix",
+            "react_wrong",
+        ] {
+            if let Ok(value) = env::var(format!("{ENV_PREFIX}{}", key.to_uppercase())) {
+                self.set(key, &value)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies `--<key> <value>` arguments, with `-` in place of `_` in the key
+    fn apply_args(&mut self, args: &[String]) -> Result<(), ConfigError> {
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if let Some(key) = arg.strip_prefix("--") {
+                let value = args
+                    .next()
+                    .ok_or(ConfigError::Invalid("argument", arg.to_string()))?;
+                if key != "config" {
+                    self.set(&key.replace('-', "_"), value)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks values and resolves channel bindings
+    pub fn validate(&mut self) -> Result<(), ConfigError> {
+        for dictionary in [&self.uk_dictionary, &self.en_dictionary] {
+            if !dictionary.is_file() {
+                return Err(ConfigError::MissingDictionary(dictionary.clone()));
+            }
+        }
+        if self.prefix.is_empty() || self.prefix.contains(char::is_whitespace) {
+            return Err(ConfigError::Invalid(
+                "prefix",
+                format!("{:?} must be non-empty and have no spaces", self.prefix),
+            ));
+        }
+        if self.guess_prefix.is_empty() || self.guess_prefix.contains(char::is_whitespace) {
+            return Err(ConfigError::Invalid(
+                "guess_prefix",
+                format!(
+                    "{:?} must be non-empty and have no spaces",
+                    self.guess_prefix
+                ),
+            ));
+        }
+        if self.guess_prefix.starts_with(&self.prefix) {
+            return Err(ConfigError::Invalid(
+                "guess_prefix",
+                format!(
+                    "{:?} must not start with the command prefix {:?}",
+                    self.guess_prefix, self.prefix
+                ),
+            ));
+        }
+        self.languages.clear();
+        for (channel, lang) in &self.channels {
+            let id = parse("channels", channel)?;
+            let lang = Lang::from_code(lang).ok_or(ConfigError::Invalid(
+                "channels",
+                format!("{lang:?} is not a language, expected \"uk\" or \"en\""),
+            ))?;
+            self.languages.insert(ChannelId(id), lang);
+        }
+        self.typos_by_channel.clear();
+        for (channel, typos) in &self.channel_typos {
+            let id = parse("channel_typos", channel)?;
+            self.typos_by_channel.insert(ChannelId(id), *typos);
+        }
+        Ok(())
+    }
+
+    pub fn min_pause(&self) -> Duration {
+        Duration::from_secs(self.min_pause)
+    }
+
+    /// Time after the question is asked when the bot hints by itself, if it does
+    pub fn hint_after(&self) -> Option<Duration> {
+        (self.hint_after > 0).then(|| Duration::from_secs(self.hint_after))
+    }
+
+    /// Time after the hint when the bot reveals the answer and asks the next question, if it does
+    pub fn reveal_after(&self) -> Option<Duration> {
+        (self.reveal_after > 0).then(|| Duration::from_secs(self.reveal_after))
+    }
+
+    /// Time without guesses after which the bot stops hinting until somebody writes, if it does
+    pub fn pause_after(&self) -> Option<Duration> {
+        (self.pause_after > 0).then(|| Duration::from_secs(self.pause_after))
+    }
+
+    pub fn is_admin(&self, user: UserId) -> bool {
+        self.admins.contains(&user.0)
+    }
+
+    /// Commands the `user` is allowed to use
+    pub fn permission(&self, user: UserId) -> Permission {
+        if self.is_admin(user) {
+            Permission::Admin
+        } else {
+            Permission::Everyone
+        }
+    }
+
+    /// What to do with answers with typos in the `channel`
+    pub fn typos(&self, channel: ChannelId) -> Typos {
+        self.typos_by_channel
+            .get(&channel)
+            .copied()
+            .unwrap_or(self.typos)
+    }
+
+    /// Language explicitly bound to the `channel`
+    pub fn channel_lang(&self, channel: ChannelId) -> Option<Lang> {
+        self.languages.get(&channel).copied()
+    }
+}
+
+fn parse<T: std::str::FromStr>(key: &'static str, value: &str) -> Result<T, ConfigError> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| ConfigError::Invalid(key, format!("{value:?} is not a number")))
+}
+
+fn parse_bool(key: &'static str, value: &str) -> Result<bool, ConfigError> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| ConfigError::Invalid(key, format!("{value:?} is not true or false")))
+}
+
+#[test]
+fn parse_test() {
+    let mut config = Config::parse(
+        r#"
+        min_pause = 30
+        prefix = "?"
+        [channels]
+        42 = "en"
+        "#,
+    )
+    .unwrap();
+    config.uk_dictionary = PathBuf::from(file!());
+    config.en_dictionary = PathBuf::from(file!());
+    config.validate().unwrap();
+    assert_eq!(config.min_pause(), Duration::from_secs(30));
+    assert_eq!(config.prefix, "?");
+    assert_eq!(config.channel_lang(ChannelId(42)), Some(Lang::En));
+    assert_eq!(config.channel_lang(ChannelId(43)), None);
+}
+
+#[test]
+fn invalid_test() {
+    assert!(Config::parse("min_pause = \"long\"").is_err());
+    assert!(Config::parse("unknown = 1").is_err());
+    let mut config = Config::parse("[channels]\ngeneral = \"uk\"").unwrap();
+    config.uk_dictionary = PathBuf::from(file!());
+    config.en_dictionary = PathBuf::from(file!());
+    assert!(matches!(
+        config.validate(),
+        Err(ConfigError::Invalid("channels", _))
+    ));
+    config.en_dictionary = PathBuf::from("no/such.db");
+    assert!(matches!(
+        config.validate(),
+        Err(ConfigError::MissingDictionary(_))
+    ));
+}
+
+#[test]
+fn override_test() {
+    let mut config = Config::default();
+    let args = ["--min-pause", "5", "--prefix", ".", "--hint-after", "0"].map(String::from);
+    config.apply_args(&args).unwrap();
+    assert_eq!(config.min_pause, 5);
+    assert_eq!(config.hint_after(), None);
+    assert_eq!(config.reveal_after(), Some(Duration::from_secs(120)));
+    assert_eq!(config.prefix, ".");
+    assert!(matches!(
+        config.set("colour", "red"),
+        Err(ConfigError::UnknownKey(_))
+    ));
+}
+
+#[test]
+fn hint_cost_test() {
+    let config = Config::parse("hint_cost = \"25%\"").unwrap();
+    assert_eq!(config.hint_cost, HintCost::Percent(25));
+    assert_eq!(config.hint_cost.reward(10, 0), 10);
+    assert_eq!(config.hint_cost.reward(10, 1), 7);
+    assert_eq!(config.hint_cost.reward(10, 5), 0);
+    assert_eq!("2".parse(), Ok(HintCost::Points(2)));
+    assert_eq!(HintCost::Points(2).reward(5, 2), 1);
+    assert_eq!(HintCost::Points(2).reward(5, 3), 0);
+    assert!(Config::parse("hint_cost = \"120%\"").is_err());
+    assert!("-1".parse::<HintCost>().is_err());
+}
+
+#[test]
+fn typos_test() {
+    let mut config = Config::parse(
+        r#"
+        typos = "off"
+        [channel_typos]
+        42 = "accept"
+        "#,
+    )
+    .unwrap();
+    config.uk_dictionary = PathBuf::from(file!());
+    config.en_dictionary = PathBuf::from(file!());
+    config.validate().unwrap();
+    assert_eq!(config.typos(ChannelId(42)), Typos::Accept);
+    assert_eq!(config.typos(ChannelId(43)), Typos::Off);
+    config.set("typos", "almost").unwrap();
+    assert_eq!(config.typos(ChannelId(43)), Typos::Almost);
+    assert!(config.set("typos", "maybe").is_err());
+    assert!(Config::parse("[channel_typos]\n42 = \"sure\"").is_err());
+}
+
+#[test]
+fn guesses_test() {
+    let mut config = Config::parse("guesses = \"prefix\"\nreact_wrong = false").unwrap();
+    assert_eq!(config.guesses, Guesses::Prefix);
+    assert_eq!(config.guess_prefix, "=");
+    assert!(!config.react_wrong);
+    config.set("guesses", "length").unwrap();
+    config.set("react_wrong", "true").unwrap();
+    assert_eq!(config.guesses, Guesses::Length);
+    assert!(config.react_wrong);
+    assert!(config.set("guesses", "some").is_err());
+    assert!(config.set("react_wrong", "yes").is_err());
+    config.uk_dictionary = PathBuf::from(file!());
+    config.en_dictionary = PathBuf::from(file!());
+    config.guess_prefix = String::new();
+    assert!(config.validate().is_err());
+    // guesses would be taken for commands
+    for guess_prefix in ["!", "!="] {
+        config.guess_prefix = guess_prefix.into();
+        assert!(config.validate().is_err());
+    }
+    config.guess_prefix = "=!".into();
+    config.validate().unwrap();
+}
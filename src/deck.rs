@@ -0,0 +1,100 @@
+use crate::{question::Lang, transport::ChannelId};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use sqlite::{Connection, Result};
+use std::collections::HashMap;
+
+/// Order questions are dealt in to a channel, every one of them once before the next deck
+pub struct Deck {
+    // the order is shuffled with it, so it is all there is to save
+    seed: u64,
+    position: usize,
+    order: Vec<usize>,
+}
+
+impl Deck {
+    /// Deck of `len` questions shuffled with the `seed`, starting at `position`
+    pub fn new(len: usize, seed: u64, position: usize) -> Self {
+        let mut order: Vec<usize> = (0..len).collect();
+        order.shuffle(&mut StdRng::seed_from_u64(seed));
+        Deck {
+            seed,
+            position,
+            order,
+        }
+    }
+
+    /// Index of the next question out of `len`, shuffling a new deck with `rng`
+    /// when this one is dealt or was shuffled for a dictionary of another size
+    pub fn deal(&mut self, len: usize, rng: &mut impl Rng) -> Option<usize> {
+        if len == 0 {
+            return None;
+        }
+        if self.order.len() != len || self.position >= len {
+            *self = Deck::new(len, rng.gen(), 0);
+        }
+        self.position += 1;
+        Some(self.order[self.position - 1])
+    }
+}
+
+/// Decks of the `lang` questions dealt to the channels, `len` questions in each
+pub fn get_decks(db: &Connection, lang: Lang, len: usize) -> Result<HashMap<ChannelId, Deck>> {
+    let mut decks = HashMap::new();
+    let mut statement = db.prepare("SELECT * FROM decks WHERE lang == :lang")?;
+    statement.bind((":lang", lang.code()))?;
+    for row in statement.into_iter() {
+        let row = row?;
+        let channel = ChannelId(row.try_read::<i64, _>("channel")? as u64);
+        let seed = row.try_read::<i64, _>("seed")? as u64;
+        let position = row.try_read::<i64, _>("position")? as usize;
+        decks.insert(channel, Deck::new(len, seed, position));
+    }
+    Ok(decks)
+}
+
+pub fn save_deck(db: &Connection, channel: ChannelId, lang: Lang, deck: &Deck) -> Result<()> {
+    let mut statement = db.prepare(
+        "INSERT OR REPLACE INTO decks (channel, lang, seed, position) \
+         VALUES (:channel, :lang, :seed, :position)",
+    )?;
+    statement.bind(
+        &[
+            (":channel", channel.0 as i64),
+            (":seed", deck.seed as i64),
+            (":position", deck.position as i64),
+        ][..],
+    )?;
+    statement.bind((":lang", lang.code()))?;
+    statement.next()?;
+    Ok(())
+}
+
+#[test]
+fn deal_test() {
+    let mut rng = rand::thread_rng();
+    let mut deck = Deck::new(5, 1, 0);
+    let mut dealt: Vec<usize> = (0..5).filter_map(|_| deck.deal(5, &mut rng)).collect();
+    dealt.sort();
+    assert_eq!(dealt, [0, 1, 2, 3, 4]);
+    assert!(deck.deal(5, &mut rng).is_some());
+    assert_eq!(deck.position, 1);
+    assert_eq!(deck.deal(0, &mut rng), None);
+    // the dictionary changed
+    assert!(deck.deal(3, &mut rng).is_some_and(|i| i < 3));
+}
+
+#[test]
+fn decks_test() {
+    let db = sqlite::open(":memory:").unwrap();
+    crate::migrations::migrate(&db).unwrap();
+    let mut rng = rand::thread_rng();
+    let mut deck = Deck::new(10, u64::MAX, 0);
+    let first = deck.deal(10, &mut rng);
+    save_deck(&db, ChannelId(1), Lang::Uk, &deck).unwrap();
+    let next = deck.deal(10, &mut rng);
+    let mut decks = get_decks(&db, Lang::Uk, 10).unwrap();
+    assert!(get_decks(&db, Lang::En, 10).unwrap().is_empty());
+    let restored = decks.get_mut(&ChannelId(1)).unwrap();
+    assert_eq!(restored.order[0], first.unwrap());
+    assert_eq!(restored.deal(10, &mut rng), next);
+}
@@ -0,0 +1,910 @@
+use crate::{
+    answer::{self, Guess},
+    channels::{self, get_bindings, get_states, GameState},
+    commands::{self, Command, Permission},
+    config::{Config, Typos},
+    deck::{get_decks, save_deck, Deck},
+    error::{Error, Result},
+    question::{EnQuestion, IsQuestion, Lang, Question},
+    score::ScoreStore,
+    session::{
+        delete_session, get_sessions, not_too_early, save_session, GameSession, SessionRecord,
+    },
+    transport::{
+        ChannelId, ChannelInfo, ChatEvent, ChatMessage, ChatReaction, MessageId, Transport, UserId,
+    },
+};
+use rand::{rngs::ThreadRng, thread_rng, Rng};
+use sqlite::Connection;
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    fmt::Display,
+    time::SystemTime,
+};
+
+/// What the frontend should do in response to an event
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// Post `text` to the `channel`
+    Send { channel: ChannelId, text: String },
+    /// Post question `text` to the `channel` and report its id back with [Game::posted]
+    Ask { channel: ChannelId, text: String },
+    /// React to the `message` with unicode `emoji`
+    React {
+        channel: ChannelId,
+        message: MessageId,
+        emoji: String,
+    },
+}
+
+fn send(channel: ChannelId, text: impl Into<String>) -> Action {
+    Action::Send {
+        channel,
+        text: text.into(),
+    }
+}
+
+fn ask<Q: Display>(channel: ChannelId, session: &GameSession<Q>) -> Action {
+    Action::Ask {
+        channel,
+        text: session.question.to_string(),
+    }
+}
+
+fn react(message: &ChatMessage, emoji: &str) -> Action {
+    Action::React {
+        channel: message.channel,
+        message: message.id,
+        emoji: emoji.to_string(),
+    }
+}
+
+/// Next question out of `data` from the `channel` deck, which is saved to the `db`
+fn deal<'q, Q: IsQuestion>(
+    data: &'q [Q],
+    decks: &mut HashMap<ChannelId, Deck>,
+    rng: &mut ThreadRng,
+    db: &Connection,
+    channel: ChannelId,
+) -> Result<&'q Q> {
+    let deck = decks
+        .entry(channel)
+        .or_insert_with(|| Deck::new(data.len(), rng.gen(), 0));
+    let index = deck
+        .deal(data.len(), rng)
+        .ok_or(Error::NoQuestions(Q::LANG))?;
+    save_deck(db, channel, Q::LANG, deck)?;
+    Ok(&data[index])
+}
+
+/// Text which mentions the `user` in a message
+pub fn mention(user: UserId) -> String {
+    format!("<@{}>", user.0)
+}
+
+/// Replies of the game in one language
+struct Texts {
+    /// Shown in help before the commands
+    rules: &'static str,
+    /// Shown in help after the commands, with version and dictionary size
+    about: fn(&str, usize) -> String,
+    /// Correct answer by the user, with the answer and their new total score
+    correct: fn(&str, &str, i64) -> String,
+    /// Score of the user who has none
+    no_score: fn(&str) -> String,
+    /// Score of the user with their standing out of the total number of players
+    score: fn(&str, i64, usize, usize) -> String,
+    /// Hint with the reward left for the answer
+    hint: fn(&str, i64) -> String,
+    /// Nobody guessed for a while and the bot waits for somebody to write
+    paused: &'static str,
+    /// Posted before the question which replaced the one lost in a restart
+    back: &'static str,
+}
+
+const UK_TEXTS: Texts = Texts {
+    rules: "Відгадати слово за визначеням з тлумачного словника Української мови. Реєстр і навколишній текст не враховуються.\n\
+Рейтинг [вказаний в квадратних дужках після кожного питання] додається гравцю за вірну відповідь і є вищий у рідше вживаних слів.\n",
+    about: |version, words| format!("Версія **{}**. Слів в словнику: **{}**", version, words),
+    correct: |user, answer, total| {
+        format!("Вірно {}. Відповідь {}. Загальний рейтинг: {}", user, answer, total)
+    },
+    no_score: |user| format!("{} нічого ще не відгадано...", user),
+    score: |user, score, standing, total| {
+        format!("{} має {} очок і є {} зі {}", user, score, standing, total)
+    },
+    hint: |hint, reward| format!("{} [+{}]", hint, reward),
+    paused: "Давно немає відповідей, тож підказок не буде, поки хтось не напише.",
+    back: "Бот повернувся, поточне питання:",
+};
+
+const EN_TEXTS: Texts = Texts {
+    rules: "Guess the word by it's definition. Answer must include exact word. Register and surrounding text are ignored.\n\
+Each question have a score [in square braces], which on correct answer is added to first player's tally.\n",
+    about: |version, words| format!("Version **{}**. Total words count: **{}**", version, words),
+    correct: |user, answer, total| {
+        format!("Correct {}. Answer is **{}**. Your total score: {}", user, answer, total)
+    },
+    no_score: |user| format!("{} has not scored yet...", user),
+    score: |user, score, standing, total| {
+        format!("{} have {} point and is {} out of {}", user, score, standing, total)
+    },
+    hint: |hint, reward| format!("{} [{} point(s)]", hint, reward),
+    paused: "No guesses for a while, so no more hints until somebody writes.",
+    back: "The bot is back, current question is:",
+};
+
+fn texts(lang: Lang) -> &'static Texts {
+    match lang {
+        Lang::Uk => &UK_TEXTS,
+        Lang::En | Lang::Uknown => &EN_TEXTS,
+    }
+}
+
+/// Counts the `hint` as given in the `session` and shows the reward left after it
+fn give_hint<Q: IsQuestion>(config: &Config, session: &mut GameSession<Q>, hint: &str) -> String {
+    session.hint();
+    let reward = config
+        .hint_cost
+        .reward(session.question.score(), session.hints);
+    (texts(Q::LANG).hint)(hint, reward)
+}
+
+/// Questions of one language and the channels they are played in
+struct Dictionary<'a, Q> {
+    data: &'a [Q],
+    sessions: HashMap<ChannelId, GameSession<'a, Q>>,
+    // order questions are dealt in to each channel
+    decks: HashMap<ChannelId, Deck>,
+    // sessions as they were last saved to the database
+    saved: HashMap<ChannelId, SessionRecord>,
+    rng: ThreadRng,
+}
+
+impl<'a, Q: IsQuestion> Dictionary<'a, Q> {
+    fn new(data: &'a [Q], decks: HashMap<ChannelId, Deck>) -> Self {
+        Dictionary {
+            data,
+            sessions: HashMap::new(),
+            decks,
+            saved: HashMap::new(),
+            rng: thread_rng(),
+        }
+    }
+
+    /// Saves sessions which changed since they were saved last time to the `db`
+    fn save(&mut self, db: &Connection) -> Result<()> {
+        for (channel, session) in &self.sessions {
+            let record = session.record();
+            if self.saved.get(channel) != Some(&record) {
+                save_session(db, *channel, Q::LANG, &record)?;
+                self.saved.insert(*channel, record);
+            }
+        }
+        let gone: Vec<ChannelId> = self
+            .saved
+            .keys()
+            .filter(|channel| !self.sessions.contains_key(channel))
+            .copied()
+            .collect();
+        for channel in gone {
+            delete_session(db, channel, Q::LANG)?;
+            self.saved.remove(&channel);
+        }
+        Ok(())
+    }
+
+    /// Carries on sessions saved to the `db`, with new questions announced in channels
+    /// where the saved one is not in the dictionary any more
+    fn restore(&mut self, db: &Connection) -> Result<Vec<Action>> {
+        let mut actions = vec![];
+        for (channel, record) in get_sessions(db, Q::LANG)? {
+            let question = self.data.iter().find(|q| q.source_id() == record.question);
+            if let Some(question) = question {
+                self.sessions
+                    .insert(channel, GameSession::restore(question, &record));
+                self.saved.insert(channel, record);
+            } else {
+                let question = deal(self.data, &mut self.decks, &mut self.rng, db, channel)?;
+                let session = self
+                    .sessions
+                    .entry(channel)
+                    .or_insert(GameSession::new(question));
+                actions.push(send(channel, texts(Q::LANG).back));
+                actions.push(ask(channel, session));
+            }
+        }
+        Ok(actions)
+    }
+
+    /// Remembers the `message` current question of the `channel` was posted with
+    fn posted(&mut self, channel: ChannelId, message: MessageId) {
+        if let Some(session) = self.sessions.get_mut(&channel) {
+            session.message = Some(message);
+        }
+    }
+
+    /// Handles ❓ reaction to the question in the `channel`
+    fn handle_reaction(&mut self, config: &Config, channel: ChannelId) -> Vec<Action> {
+        let mut actions = vec![];
+        if let Some(session) = self.sessions.get_mut(&channel) {
+            if not_too_early(session.asked, config.min_pause()) {
+                if let Some(hint) = session.question.hint(session.hints + 1) {
+                    actions.push(send(channel, give_hint(config, session, &hint)));
+                }
+            }
+        }
+        actions
+    }
+
+    /// Asks the question of the `channel` again, or a new one if `fresh` or there is none yet
+    fn start(&mut self, db: &Connection, channel: ChannelId, fresh: bool) -> Result<Action> {
+        if fresh {
+            self.sessions.remove(&channel);
+        }
+        let session = match self.sessions.entry(channel) {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => e.insert(GameSession::new(deal(
+                self.data,
+                &mut self.decks,
+                &mut self.rng,
+                db,
+                channel,
+            )?)),
+        };
+        // timers count from the question being asked again
+        session.asked = SystemTime::now();
+        session.guessed = session.asked;
+        session.active = true;
+        Ok(ask(channel, session))
+    }
+
+    /// Hints and reveals answers in running channels where nobody asked for it in time
+    fn timers(
+        &mut self,
+        config: &Config,
+        db: &Connection,
+        states: &HashMap<ChannelId, GameState>,
+        now: SystemTime,
+    ) -> Result<Vec<Action>> {
+        let mut actions = vec![];
+        for (channel, session) in self.sessions.iter_mut() {
+            let stopped = states
+                .get(channel)
+                .is_some_and(|s| *s != GameState::Running);
+            if stopped || !session.active {
+                continue;
+            }
+            let idle = now.duration_since(session.guessed).unwrap_or_default();
+            if config.pause_after().is_some_and(|after| idle >= after) {
+                // keeps the question until somebody writes again
+                session.active = false;
+                actions.push(send(*channel, texts(Q::LANG).paused));
+                continue;
+            }
+            let waited = now.duration_since(session.asked).unwrap_or_default();
+            let hint = session.question.hint(session.hints + 1);
+            if let Some(hint) =
+                hint.filter(|_| config.hint_after().is_some_and(|after| waited >= after))
+            {
+                actions.push(send(*channel, give_hint(config, session, &hint)));
+            } else if session.hints > 0
+                && config.reveal_after().is_some_and(|after| waited >= after)
+            {
+                actions.push(send(*channel, session.question.get_answer()));
+                session.next(deal(
+                    self.data,
+                    &mut self.decks,
+                    &mut self.rng,
+                    db,
+                    *channel,
+                )?);
+                // nobody is left to play until somebody writes again
+                session.active = false;
+                actions.push(ask(*channel, session));
+            }
+        }
+        Ok(actions)
+    }
+
+    /// Handles the `message`, which is a `command` or an answer attempt if it is `None`
+    fn handle_message(
+        &mut self,
+        config: &Config,
+        db: &Connection,
+        scores: &dyn ScoreStore,
+        message: &ChatMessage,
+        command: Option<Option<Command>>,
+        permission: Permission,
+    ) -> Result<Vec<Action>> {
+        let texts = texts(Q::LANG);
+        if !message.bot {
+            if let Some(session) = self.sessions.get_mut(&message.channel) {
+                session.activate();
+            }
+        }
+        // help and scores need no question, so there may be none, e.g. in a stopped game
+        match command {
+            Some(Some(Command::Score)) => {
+                let (score, standing, total) = scores.get_score(message.author.0)?;
+                let user = mention(message.author);
+                let text = if score == 0 {
+                    (texts.no_score)(&user)
+                } else {
+                    (texts.score)(&user, score, standing, total)
+                };
+                return Ok(vec![send(message.channel, text)]);
+            }
+            Some(Some(Command::Top)) => {
+                return Ok(vec![send(message.channel, top_report(scores)?)]);
+            }
+            Some(Some(Command::Help)) => {
+                let help = format!(
+                    "{}{}{}",
+                    texts.rules,
+                    commands::help(Q::LANG, &config.prefix, permission),
+                    (texts.about)(env!("CARGO_PKG_VERSION"), self.data.len())
+                );
+                return Ok(vec![send(message.channel, help)]);
+            }
+            _ => {}
+        }
+        let mut actions = vec![];
+        let session = match self.sessions.entry(message.channel) {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => {
+                let session = e.insert(GameSession::new(deal(
+                    self.data,
+                    &mut self.decks,
+                    &mut self.rng,
+                    db,
+                    message.channel,
+                )?));
+                // nobody has seen the question yet, so the message is not taken for a guess at it
+                return Ok(vec![ask(message.channel, session)]);
+            }
+        };
+        if let Some(command) = command {
+            match command {
+                Some(Command::Next) => {
+                    if session.hints > 0 || not_too_early(session.asked, config.min_pause()) {
+                        actions.push(send(message.channel, session.question.get_answer()));
+                        session.next(deal(
+                            self.data,
+                            &mut self.decks,
+                            &mut self.rng,
+                            db,
+                            message.channel,
+                        )?);
+                        actions.push(ask(message.channel, session));
+                    } else {
+                        actions.push(react(message, "⏱️"));
+                    }
+                }
+                Some(Command::Question) => {
+                    actions.push(ask(message.channel, session));
+                }
+                Some(Command::Hint) => match session.question.hint(session.hints + 1) {
+                    Some(hint) if not_too_early(session.asked, config.min_pause()) => {
+                        actions.push(send(message.channel, give_hint(config, session, &hint)));
+                    }
+                    Some(_) => actions.push(react(message, "⏱️")),
+                    None => actions.push(react(message, "🛑")),
+                },
+                _ => {}
+            }
+        } else {
+            let answer = session.question.get_answer();
+            let guess = answer::guess(&message.content, answer);
+            let typos = config.typos(message.channel);
+            let accepted = matches!(
+                (guess, typos),
+                (Guess::Correct, _) | (Guess::Close, Typos::Accept)
+            );
+            let is_guess = !message.bot
+                && answer::is_guess(
+                    &message.content,
+                    answer,
+                    config.guesses,
+                    &config.guess_prefix,
+                );
+            if accepted || is_guess {
+                session.guessed = SystemTime::now();
+            }
+            // answers are taken from any message, but only guesses are told they are wrong
+            if !accepted {
+                if is_guess && guess == Guess::Close && typos == Typos::Almost {
+                    actions.push(react(message, "🤏"));
+                } else if is_guess && config.react_wrong {
+                    actions.push(react(message, "➖"));
+                }
+                return Ok(actions);
+            }
+            // ansver verify and update score
+            // every hint taken makes the answer worth less,
+            // a near miss is rewarded as if it took one more hint
+            let hints = session.hints + usize::from(guess == Guess::Close);
+            let reward = config.hint_cost.reward(session.question.score(), hints);
+            let new_score = scores.increment_score(message.author.0, reward)?;
+            actions.push(send(
+                message.channel,
+                (texts.correct)(
+                    &mention(message.author),
+                    session.question.get_answer(),
+                    new_score,
+                ),
+            ));
+            // reset asked time and hints
+            session.next(deal(
+                self.data,
+                &mut self.decks,
+                &mut self.rng,
+                db,
+                message.channel,
+            )?);
+            actions.push(ask(message.channel, session));
+        }
+        Ok(actions)
+    }
+}
+
+/// Questions, scores and sessions of all the channels the game is played in
+pub struct Game<'a> {
+    config: &'a Config,
+    // the bot itself, as reported by the chat service
+    user: UserId,
+    db: &'a Connection,
+    // scores are in the `db` unless they were given a store of their own
+    scores: &'a dyn ScoreStore,
+    // channels bound with `bind` command
+    bindings: HashMap<ChannelId, Lang>,
+    // channels where the game was started, stopped or paused
+    states: HashMap<ChannelId, GameState>,
+    uk: Dictionary<'a, Question>,
+    en: Dictionary<'a, EnQuestion>,
+}
+
+impl<'a> Game<'a> {
+    pub fn new(
+        config: &'a Config,
+        user: UserId,
+        db: &'a Connection,
+        data_uk: &'a [Question],
+        data_en: &'a [EnQuestion],
+    ) -> Result<Self> {
+        Ok(Game {
+            config,
+            user,
+            db,
+            scores: db,
+            bindings: get_bindings(db)?,
+            states: get_states(db)?,
+            uk: Dictionary::new(data_uk, get_decks(db, Lang::Uk, data_uk.len())?),
+            en: Dictionary::new(data_en, get_decks(db, Lang::En, data_en.len())?),
+        })
+    }
+
+    /// Keeps scores in the `scores` store rather than the database of the game
+    pub fn with_scores(mut self, scores: &'a dyn ScoreStore) -> Self {
+        self.scores = scores;
+        self
+    }
+
+    /// Looks up whatever the engine needs to know about the `event` through the `transport`,
+    /// handles it and performs resulting actions.
+    /// On failure the message which caused the event is marked with ⚠️ if possible.
+    pub fn handle_event<T: Transport>(&mut self, transport: &T, event: ChatEvent) -> Result<()> {
+        let (channel, message) = match &event {
+            ChatEvent::Reaction(reaction) if reaction.emoji == "❓" => {
+                (reaction.channel, reaction.message)
+            }
+            ChatEvent::Message(message) => (message.channel, message.id),
+            _ => return Ok(()),
+        };
+        let result = self.process(transport, event);
+        if result.is_err() {
+            // the failure is reported to the caller, this one is best effort
+            let _ = transport.react(channel, message, "⚠️");
+        }
+        result
+    }
+
+    fn process<T: Transport>(&mut self, transport: &T, event: ChatEvent) -> Result<()> {
+        let actions = match event {
+            ChatEvent::Reaction(reaction) => {
+                let channel = transport
+                    .channel(reaction.channel)
+                    .map_err(Error::transport)?;
+                let author = transport
+                    .message_author(reaction.channel, reaction.message)
+                    .map_err(Error::transport)?;
+                self.handle_reaction(&reaction, &channel, author)?
+            }
+            ChatEvent::Message(message) => {
+                let channel = transport
+                    .channel(message.channel)
+                    .map_err(Error::transport)?;
+                self.handle_message(&message, &channel)?
+            }
+            ChatEvent::Other => vec![],
+        };
+        let applied = self.apply(transport, actions);
+        self.save()?;
+        applied
+    }
+
+    /// Saves sessions which changed, so that they are carried on after a restart
+    fn save(&mut self) -> Result<()> {
+        self.uk.save(self.db)?;
+        self.en.save(self.db)
+    }
+
+    /// Carries on sessions of the previous run through the `transport`,
+    /// should be called once before handling events
+    pub fn restore<T: Transport>(&mut self, transport: &T) -> Result<()> {
+        let mut actions = self.uk.restore(self.db)?;
+        actions.extend(self.en.restore(self.db)?);
+        let applied = self.apply(transport, actions);
+        self.save()?;
+        applied
+    }
+
+    /// Performs hints and answer reveals which are due by now through the `transport`,
+    /// should be called every few seconds
+    pub fn tick<T: Transport>(&mut self, transport: &T) -> Result<()> {
+        let actions = self.timers(SystemTime::now())?;
+        let applied = self.apply(transport, actions);
+        self.save()?;
+        applied
+    }
+
+    /// Hints and answer reveals which are due by `now`
+    pub fn timers(&mut self, now: SystemTime) -> Result<Vec<Action>> {
+        let mut actions = self.uk.timers(self.config, self.db, &self.states, now)?;
+        actions.extend(self.en.timers(self.config, self.db, &self.states, now)?);
+        Ok(actions)
+    }
+
+    /// Performs `actions` through the `transport`.
+    /// A failed action does not stop the rest, the first error is returned.
+    pub fn apply<T: Transport>(&mut self, transport: &T, actions: Vec<Action>) -> Result<()> {
+        let mut result = Ok(());
+        for action in actions {
+            let done = match action {
+                Action::Send { channel, text } => transport.send_text(channel, &text).map(drop),
+                Action::Ask { channel, text } => transport
+                    .send_text(channel, &text)
+                    .map(|id| self.posted(channel, id)),
+                Action::React {
+                    channel,
+                    message,
+                    emoji,
+                } => transport.react(channel, message, &emoji),
+            };
+            if let (Err(e), Ok(())) = (done, &result) {
+                result = Err(Error::transport(e));
+            }
+        }
+        result
+    }
+
+    /// Language the `channel` was bound to with `bind` command or in config,
+    /// or guessed by "uk" or "en" part of its name, like "game-uk", if that is allowed
+    pub fn channel_lang(&self, channel: &ChannelInfo) -> Lang {
+        if let Some(lang) = self.bindings.get(&channel.id) {
+            return *lang;
+        }
+        if let Some(lang) = self.config.channel_lang(channel.id) {
+            return lang;
+        }
+        let has_part = |code| match &channel.name {
+            // "general" is not English
+            Some(n) => n.split(['-', '_']).any(|part| part == code),
+            None => false,
+        };
+        match self.config.match_channel_names {
+            true if has_part("uk") => Lang::Uk,
+            true if has_part("en") => Lang::En,
+            _ => Lang::Uknown,
+        }
+    }
+
+    /// Binds the `channel` to the language with `code` or unbinds it if there is none
+    fn bind(
+        &mut self,
+        message: &ChatMessage,
+        channel: &ChannelInfo,
+        code: Option<&str>,
+    ) -> Result<Action> {
+        let before = self.channel_lang(channel);
+        match code.map(Lang::from_code) {
+            Some(Some(lang)) => {
+                channels::bind(self.db, channel.id, lang)?;
+                self.bindings.insert(channel.id, lang);
+            }
+            Some(None) => return Ok(react(message, "❌")),
+            None => {
+                channels::unbind(self.db, channel.id)?;
+                self.bindings.remove(&channel.id);
+            }
+        }
+        if self.channel_lang(channel) != before {
+            self.uk.sessions.remove(&channel.id);
+            self.en.sessions.remove(&channel.id);
+        }
+        Ok(react(message, "✅"))
+    }
+
+    /// State of the game in the `channel`, it runs unless it was stopped or paused
+    pub fn state(&self, channel: ChannelId) -> GameState {
+        self.states
+            .get(&channel)
+            .copied()
+            .unwrap_or(GameState::Running)
+    }
+
+    /// Starts, stops, pauses or resumes the game in the `channel` with the `command`
+    fn set_state(
+        &mut self,
+        message: &ChatMessage,
+        channel: &ChannelInfo,
+        command: Command,
+    ) -> Result<Vec<Action>> {
+        let state = match command {
+            Command::Start | Command::Resume => GameState::Running,
+            Command::Pause => GameState::Paused,
+            _ => GameState::Stopped,
+        };
+        channels::set_state(self.db, channel.id, state)?;
+        self.states.insert(channel.id, state);
+        let mut actions = vec![react(message, "✅")];
+        match command {
+            Command::Stop => {
+                self.uk.sessions.remove(&channel.id);
+                self.en.sessions.remove(&channel.id);
+            }
+            Command::Start | Command::Resume => {
+                let fresh = command == Command::Start;
+                match self.channel_lang(channel) {
+                    Lang::Uk => actions.push(self.uk.start(self.db, channel.id, fresh)?),
+                    Lang::En => actions.push(self.en.start(self.db, channel.id, fresh)?),
+                    Lang::Uknown => {}
+                }
+            }
+            _ => {}
+        }
+        Ok(actions)
+    }
+
+    /// Remembers the `message` current question of the `channel` was posted with
+    pub fn posted(&mut self, channel: ChannelId, message: MessageId) {
+        self.uk.posted(channel, message);
+        self.en.posted(channel, message);
+    }
+
+    /// Handles `reaction` in the `channel` to a message written by `target_author`
+    pub fn handle_reaction(
+        &mut self,
+        reaction: &ChatReaction,
+        channel: &ChannelInfo,
+        target_author: UserId,
+    ) -> Result<Vec<Action>> {
+        // hints are only given while the game is running, like with the command
+        if reaction.emoji != "❓"
+            || target_author != self.user
+            || self.state(channel.id) != GameState::Running
+        {
+            return Ok(vec![]);
+        }
+        Ok(match self.channel_lang(channel) {
+            Lang::Uk => self.uk.handle_reaction(self.config, reaction.channel),
+            Lang::En => self.en.handle_reaction(self.config, reaction.channel),
+            // the game is not played in channels without a language
+            Lang::Uknown => vec![],
+        })
+    }
+
+    /// Handles `message` posted to the `channel`
+    pub fn handle_message(
+        &mut self,
+        message: &ChatMessage,
+        channel: &ChannelInfo,
+    ) -> Result<Vec<Action>> {
+        if message.author == self.user {
+            return Ok(vec![]);
+        }
+        let text = message
+            .content
+            .to_owned()
+            .trim()
+            .replace(' ', "")
+            .to_lowercase();
+        let lang = self.channel_lang(channel);
+        let permission = self.config.permission(message.author);
+        // `Some(None)` is an unknown command
+        let command = text
+            .strip_prefix(self.config.prefix.to_lowercase().as_str())
+            .map(|command| commands::find(command, lang));
+        if let Some(Some((spec, arg))) = command {
+            if spec.permission > permission {
+                return Ok(vec![react(message, "🛑")]);
+            }
+            // admin commands, available in any channel
+            match spec.command {
+                Command::Bind => return Ok(vec![self.bind(message, channel, Some(arg))?]),
+                Command::Unbind => return Ok(vec![self.bind(message, channel, None)?]),
+                Command::Start | Command::Stop | Command::Pause | Command::Resume => {
+                    return self.set_state(message, channel, spec.command)
+                }
+                _ => {}
+            }
+        }
+        // game commands and answers
+        let command = command.map(|command| command.map(|(spec, _)| spec.command));
+        // only help and scores are there while the game is not running
+        if self.state(channel.id) != GameState::Running
+            && !matches!(
+                command,
+                Some(Some(Command::Help | Command::Score | Command::Top))
+            )
+        {
+            return Ok(match command {
+                Some(Some(_)) => vec![react(message, "⏸️")],
+                _ => vec![],
+            });
+        }
+        let (config, db, scores) = (self.config, self.db, self.scores);
+        match lang {
+            Lang::Uk => self
+                .uk
+                .handle_message(config, db, scores, message, command, permission),
+            Lang::En => self
+                .en
+                .handle_message(config, db, scores, message, command, permission),
+            Lang::Uknown => Ok(vec![]),
+        }
+    }
+}
+
+/// Top 10 players, one per line
+fn top_report(scores: &dyn ScoreStore) -> Result<String> {
+    let mut top_report = String::default();
+    scores
+        .get_top()?
+        .into_iter()
+        .enumerate()
+        .map(|(id, (user, score))| {
+            top_report.push_str(
+                format!(
+                    "{}    |    {}    |    {}\n",
+                    id + 1,
+                    mention(UserId(user)),
+                    score,
+                )
+                .as_str(),
+            );
+        })
+        .for_each(drop);
+    Ok(top_report)
+}
+
+#[cfg(test)]
+use crate::testing::{run, transport, Fixture};
+
+#[test]
+fn hint_and_next_test() {
+    let fixture = Fixture::default();
+    let (mut game, mut transport) = (fixture.game(), transport());
+    // the question comes first, then the hint is too early
+    transport.push_message(ChannelId(1), UserId(7), "!хінт");
+    let early = transport.push_message(ChannelId(1), UserId(7), "!хінт");
+    run(&mut game, &mut transport);
+    assert_eq!(transport.reactions_to(early), vec!["⏱️"]);
+
+    game.uk.sessions.get_mut(&ChannelId(1)).unwrap().asked = std::time::SystemTime::UNIX_EPOCH;
+    transport.push_message(ChannelId(1), UserId(7), "!хінт");
+    let again = transport.push_message(ChannelId(1), UserId(7), "!хінт");
+    run(&mut game, &mut transport);
+    assert_eq!(transport.reactions_to(again), vec!["⏱️"]);
+
+    game.uk.sessions.get_mut(&ChannelId(1)).unwrap().asked = std::time::SystemTime::UNIX_EPOCH;
+    transport.push_message(ChannelId(1), UserId(7), "!хінт");
+    let last = transport.push_message(ChannelId(1), UserId(7), "!хінт");
+    transport.push_message(ChannelId(1), UserId(7), "!далі");
+    run(&mut game, &mut transport);
+    assert_eq!(transport.reactions_to(last), vec!["🛑"]);
+    assert_eq!(
+        transport.texts(ChannelId(1)),
+        vec![
+            &fixture.uk[0].to_string(),
+            "◾◾◾ [+2]",
+            "к◾т [+1]",
+            "кіт",
+            &fixture.uk[0].to_string()
+        ]
+    );
+}
+
+#[test]
+fn reaction_hint_test() {
+    let fixture = Fixture::default();
+    let (mut game, mut transport) = (fixture.game(), transport());
+    transport.push_message(ChannelId(1), UserId(7), "!п");
+    run(&mut game, &mut transport);
+    let session = game.uk.sessions.get_mut(&ChannelId(1)).unwrap();
+    session.asked = std::time::SystemTime::UNIX_EPOCH;
+    let question = session.message.unwrap();
+    transport.push_reaction(ChannelId(1), question, UserId(7), "❓");
+    run(&mut game, &mut transport);
+    assert_eq!(transport.texts(ChannelId(1))[1], "◾◾◾ [+2]");
+    assert_eq!(game.uk.sessions[&ChannelId(1)].hints, 1);
+}
+
+#[test]
+fn timers_test() {
+    let fixture = Fixture::default();
+    let (mut game, mut transport) = (fixture.game(), transport());
+    transport.push_message(ChannelId(1), UserId(7), "!п");
+    run(&mut game, &mut transport);
+    let asked = game.uk.sessions[&ChannelId(1)].asked;
+    assert!(game
+        .timers(asked + std::time::Duration::from_secs(60))
+        .unwrap()
+        .is_empty());
+
+    let hint = game
+        .timers(asked + std::time::Duration::from_secs(120))
+        .unwrap();
+    assert_eq!(hint, vec![send(ChannelId(1), "◾◾◾ [+2]")]);
+    let hinted = game.uk.sessions[&ChannelId(1)].asked;
+    let hint = game
+        .timers(hinted + std::time::Duration::from_secs(120))
+        .unwrap();
+    assert_eq!(hint, vec![send(ChannelId(1), "к◾т [+1]")]);
+    let hinted = game.uk.sessions[&ChannelId(1)].asked;
+    let reveal = game
+        .timers(hinted + std::time::Duration::from_secs(120))
+        .unwrap();
+    assert_eq!(reveal[0], send(ChannelId(1), "кіт"));
+    assert!(matches!(reveal[1], Action::Ask { .. }));
+
+    // nobody played since, so the bot stays quiet
+    let asked = game.uk.sessions[&ChannelId(1)].asked;
+    assert!(game
+        .timers(asked + std::time::Duration::from_secs(600))
+        .unwrap()
+        .is_empty());
+    transport.push_message(ChannelId(1), UserId(7), "пес");
+    run(&mut game, &mut transport);
+    assert_eq!(
+        game.timers(asked + std::time::Duration::from_secs(600))
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn pause_after_test() {
+    let mut fixture = Fixture::default();
+    fixture.config.pause_after = 300;
+    let (mut game, mut transport) = (fixture.game(), transport());
+    transport.push_message(ChannelId(1), UserId(7), "пес");
+    run(&mut game, &mut transport);
+    let guessed = game.uk.sessions[&ChannelId(1)].guessed;
+    let paused = game
+        .timers(guessed + std::time::Duration::from_secs(300))
+        .unwrap();
+    assert_eq!(paused, vec![send(ChannelId(1), UK_TEXTS.paused)]);
+    assert!(!game.uk.sessions[&ChannelId(1)].active);
+    assert!(game
+        .timers(guessed + std::time::Duration::from_secs(600))
+        .unwrap()
+        .is_empty());
+
+    // writing brings the bot back, counting from then
+    transport.push_message(ChannelId(1), UserId(7), "привіт");
+    run(&mut game, &mut transport);
+    let session = &game.uk.sessions[&ChannelId(1)];
+    assert!(session.active && session.guessed > guessed);
+}
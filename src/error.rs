@@ -0,0 +1,46 @@
+use crate::question::Lang;
+use std::fmt::Display;
+
+#[derive(Debug)]
+pub enum Error {
+    /// Request to the chat service failed
+    Transport(Box<dyn std::error::Error>),
+    /// Database query failed
+    Db(sqlite::Error),
+    /// Dictionary for the language has no questions
+    NoQuestions(Lang),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    pub fn transport(e: impl std::error::Error + 'static) -> Self {
+        Error::Transport(Box::new(e))
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Transport(e) => write!(f, "chat request failed: {}", e),
+            Error::Db(e) => write!(f, "database query failed: {}", e),
+            Error::NoQuestions(lang) => write!(f, "no questions for {:?} language", lang),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Transport(e) => Some(e.as_ref()),
+            Error::Db(e) => Some(e),
+            Error::NoQuestions(_) => None,
+        }
+    }
+}
+
+impl From<sqlite::Error> for Error {
+    fn from(e: sqlite::Error) -> Self {
+        Error::Db(e)
+    }
+}
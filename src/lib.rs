@@ -0,0 +1,25 @@
+extern crate rand;
+extern crate regex;
+extern crate serde;
+extern crate sqlite;
+extern crate toml;
+extern crate unicode_normalization;
+
+pub mod answer;
+pub mod backoff;
+pub mod channels;
+pub mod commands;
+pub mod config;
+pub mod deck;
+pub mod engine;
+pub mod error;
+pub mod migrations;
+pub mod question;
+pub mod score;
+pub mod session;
+pub mod state;
+pub mod testing;
+pub mod transport;
+
+pub use engine::{Action, Game};
+pub use transport::Transport;
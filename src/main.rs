@@ -1,687 +1,269 @@
 extern crate discord;
-extern crate rand;
-extern crate regex;
+extern crate gamebot;
 extern crate sqlite;
 
 use discord::{
-    model::{Channel, Event, Message, ReactionEmoji},
+    model::{self, Channel, Event, ReactionEmoji, ReadyEvent},
     Discord,
 };
-use rand::{seq::SliceRandom, thread_rng, Rng};
-use regex::Regex;
-use sqlite::{Connection, Row};
-use std::{env, fmt::Display, time::SystemTime};
-
-const MIN_PAUSE: u128 = 60_000; // 1 MIN between hints and next questions in millis
-
-enum Lang {
-    Uk,
-    En,
-    Uknown,
-}
-
-trait IsQuestion {
-    fn get_answer(&self) -> &str;
-}
-
-struct Question {
-    question: String,
-    answer: String,
-    score: i64,
-    bold: Regex,
-}
+use gamebot::{
+    backoff::Backoff,
+    config::Config,
+    question::{load_en, load_uk},
+    state,
+    transport::{ChannelId, ChannelInfo, ChatEvent, ChatMessage, ChatReaction, MessageId, UserId},
+    Game, Transport,
+};
+use std::{
+    env,
+    fmt::Display,
+    process,
+    sync::mpsc::{self, Receiver, RecvTimeoutError, Sender},
+    thread,
+    time::Duration,
+};
 
-impl Question {
-    fn new(question: String, answer: String, score: i64) -> Self {
-        Question {
-            question,
-            answer,
-            score,
-            bold: Regex::new(r"(\[B\])|(\[\/B])").unwrap(),
+// How long to wait for an event before checking game timers
+const TICK: Duration = Duration::from_secs(5);
+// Delays between attempts to connect to the gateway
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(300);
+// Close codes of the gateway reconnecting does not help with: authentication failed,
+// invalid shard, sharding required, invalid API version, invalid or disallowed intents
+const FATAL_CLOSE_CODES: [u16; 6] = [4004, 4010, 4011, 4012, 4013, 4014];
+
+/// Exits if the gateway closed with the `err` which would only repeat on reconnect
+fn check_fatal(err: &discord::Error) {
+    if let discord::Error::Closed(Some(code), body) = err {
+        if FATAL_CLOSE_CODES.contains(code) {
+            exit("Gateway", format!("closed with code {}: {}", code, body));
         }
     }
 }
 
-impl Display for Question {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_fmt(format_args!(
-            "**{}** ({} літер) [+{}]",
-            self.bold.replace_all(&self.question, ""),
-            self.answer.chars().count(),
-            self.score
-        ))
+/// Connects to the gateway, retrying with growing delays until it succeeds.
+/// The delays start over only once the connection delivers events,
+/// as the gateway may close it right away.
+fn connect(discord: &Discord, backoff: &mut Backoff) -> (discord::Connection, ReadyEvent) {
+    loop {
+        match discord.connect() {
+            Ok(connected) => return connected,
+            Err(err) => {
+                check_fatal(&err);
+                let delay = backoff.next_delay();
+                println!("Connect failed: {:?}, retrying in {:?}", err, delay);
+                thread::sleep(delay);
+            }
+        }
     }
 }
 
-impl IsQuestion for Question {
-    fn get_answer(&self) -> &str {
-        &self.answer
-    }
+/// Reports fatal startup error and exits
+fn exit(what: &str, err: impl Display) -> ! {
+    eprintln!("{} error: {}", what, err);
+    process::exit(1);
 }
 
-struct EnQuestion {
-    question: String,
-    answer: String,
-    score: i64,
+/// [Transport] over Discord gateway and REST API
+struct DiscordTransport {
+    discord: Discord,
+    // events received by `read_gateway` thread
+    events: Receiver<ChatEvent>,
 }
 
-impl EnQuestion {
-    fn new(r: &Row) -> Self {
-        let mut rng = thread_rng();
-        let mut question: String = r
-            .read::<&str, _>("definition")
-            .chars()
-            .filter(|c| c.is_alphanumeric() || c.eq(&' '))
-            .collect();
-        question = question
-            .split(' ')
-            .filter(|s| !s.is_empty())
-            .collect::<Vec<_>>()
-            .join(" ");
-        if question.contains('.') {
-            question = question.split_once('.').unwrap().0.to_string() // first part before '.'
-        }
-        let answer = r.read::<&str, _>("word").to_string().to_lowercase();
-        let score = rng.gen_range(1..5);
-        EnQuestion {
-            question,
-            answer,
-            score,
-        }
-    }
-}
+impl Transport for DiscordTransport {
+    type Error = discord::Error;
 
-impl Display for EnQuestion {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_fmt(format_args!(
-            "**{}** ({} letters) [{} point(s)]",
-            self.question,
-            self.answer.chars().count(),
-            self.score
-        ))
+    fn send_text(&self, channel: ChannelId, text: &str) -> discord::Result<MessageId> {
+        self.discord
+            .send_message(model::ChannelId(channel.0), text, "", false)
+            .map(|m| MessageId(m.id.0))
     }
-}
 
-impl IsQuestion for EnQuestion {
-    fn get_answer(&self) -> &str {
-        &self.answer
+    fn react(&self, channel: ChannelId, message: MessageId, emoji: &str) -> discord::Result<()> {
+        self.discord.add_reaction(
+            model::ChannelId(channel.0),
+            model::MessageId(message.0),
+            ReactionEmoji::Unicode(emoji.into()),
+        )
     }
-}
-
-fn next_question(r: &Row) -> Question {
-    let new_answer = r.read::<&str, _>("word").replace(|c: char| c == '\"', "");
-    Question::new(
-        r.read::<&str, _>("interpretation").to_string(),
-        new_answer,
-        r.read::<i64, _>("id_syn"),
-    )
-}
 
-fn get_score(db: &Connection, user: u64) -> (i64, usize, usize) {
-    let q = format!("SELECT * FROM scores WHERE user == {user}");
-    if let Some(Ok(data)) = db.prepare(&q).unwrap().into_iter().last() {
-        let score = data.read::<i64, _>("score");
-        let mut all_scores = db
-            .prepare("SELECT * FROM scores")
-            .unwrap()
-            .into_iter()
-            .map(|r| r.unwrap().read::<i64, _>("score"))
-            .collect::<Vec<i64>>();
-        all_scores.sort();
-        all_scores.reverse();
-        let standing = all_scores.iter().position(|n| *n == score).unwrap() + 1;
-        (score, standing, all_scores.len())
-    } else {
-        (0, 0, 0)
+    fn channel(&self, channel: ChannelId) -> discord::Result<ChannelInfo> {
+        self.discord
+            .get_channel(model::ChannelId(channel.0))
+            .map(|c| ChannelInfo {
+                id: channel,
+                name: match c {
+                    Channel::Public(c) => Some(c.name),
+                    _ => None,
+                },
+            })
     }
-}
-
-fn get_top(db: &Connection) -> Vec<(i64, i64)> {
-    let mut accum = Vec::with_capacity(10);
-    db.prepare("SELECT * FROM scores ORDER BY score DESC LIMIT 10")
-        .unwrap()
-        .into_iter()
-        .map(|r| {
-            let row = r.unwrap();
-            let score = row.read::<i64, _>("score");
-            let user = row.read::<i64, _>("user");
-            accum.push((user, score));
-        })
-        .for_each(drop);
-    accum
-}
 
-fn increment_score(db: &Connection, user: u64, score: i64) -> i64 {
-    let current = format!("SELECT * FROM scores WHERE user == {user}");
-    let ignore_if_exist = format!("INSERT OR IGNORE INTO scores (user, score) VALUES ({user}, 0)");
-    db.execute(ignore_if_exist).unwrap();
-    let data = db
-        .prepare(&current)
-        .unwrap()
-        .into_iter()
-        .last()
-        .unwrap()
-        .unwrap();
-    let total_score = data.read::<i64, _>("score") + score;
-    let insert = format!("INSERT OR REPLACE INTO scores VALUES ({user}, {total_score})");
-    let _ = db.execute(&insert).unwrap();
-    total_score
-}
+    fn message_author(&self, channel: ChannelId, message: MessageId) -> discord::Result<UserId> {
+        self.discord
+            .get_message(model::ChannelId(channel.0), model::MessageId(message.0))
+            .map(|m| UserId(m.author.id.0))
+    }
 
-fn produce_hint<T>(q: &T) -> String
-where
-    T: IsQuestion,
-{
-    let answer = q.get_answer().chars();
-    let mut hint = answer.clone().into_iter().rev().last().unwrap().to_string();
-    hint.push_str(&str::repeat("◾", answer.clone().count() - 2));
-    hint.push(answer.last().unwrap());
-    hint
+    fn recv_event(&mut self, timeout: Duration) -> discord::Result<Option<ChatEvent>> {
+        match self.events.recv_timeout(timeout) {
+            Ok(event) => Ok(Some(event)),
+            Err(RecvTimeoutError::Timeout) => Ok(None),
+            Err(RecvTimeoutError::Disconnected) => {
+                Err(discord::Error::Other("gateway reader stopped"))
+            }
+        }
+    }
 }
 
-fn react_timer(message: &Message, discord: &Discord) {
-    drop(discord.add_reaction(
-        message.channel_id,
-        message.id,
-        ReactionEmoji::Unicode("⏱️".into()),
-    ));
+/// Event of the game out of gateway `event`
+fn chat_event(event: Event) -> ChatEvent {
+    match event {
+        Event::MessageCreate(m) => ChatEvent::Message(ChatMessage {
+            id: MessageId(m.id.0),
+            channel: ChannelId(m.channel_id.0),
+            author: UserId(m.author.id.0),
+            bot: m.author.bot,
+            content: m.content,
+        }),
+        Event::ReactionAdd(r) => ChatEvent::Reaction(ChatReaction {
+            channel: ChannelId(r.channel_id.0),
+            message: MessageId(r.message_id.0),
+            user: UserId(r.user_id.0),
+            emoji: match r.emoji {
+                ReactionEmoji::Unicode(emoji) => emoji,
+                ReactionEmoji::Custom { name, .. } => name,
+            },
+        }),
+        _ => ChatEvent::Other,
+    }
 }
 
-fn react_stop(message: &Message, discord: &Discord) {
-    drop(discord.add_reaction(
-        message.channel_id,
-        message.id,
-        ReactionEmoji::Unicode("🛑".into()),
-    ));
+/// Reads gateway events into `events` until nobody receives them,
+/// replacing the connection when the gateway closes it
+fn read_gateway(
+    discord: Discord,
+    mut connection: discord::Connection,
+    mut backoff: Backoff,
+    events: Sender<ChatEvent>,
+) {
+    // The connection resumes the session by itself when it can,
+    // `Closed` means it gave up and a new one is needed.
+    // Game state outlives connections, so channels carry on with the same questions.
+    let mut delivered = false;
+    loop {
+        let event = connection.recv_event();
+        if event.is_ok() && !delivered {
+            // the connection works, a later close is not a failure to connect
+            backoff.reset();
+            delivered = true;
+        }
+        match event.map(chat_event) {
+            Ok(ChatEvent::Other) => {}
+            Ok(event) => {
+                if events.send(event).is_err() {
+                    return;
+                }
+            }
+            Err(err @ discord::Error::Closed(..)) => {
+                println!("Gateway closed on us: {:?}", err);
+                check_fatal(&err);
+                let delay = backoff.next_delay();
+                println!("Reconnecting in {:?}", delay);
+                thread::sleep(delay);
+                let (new_connection, ready) = connect(&discord, &mut backoff);
+                println!("Reconnected as {}.", ready.user.username);
+                connection = new_connection;
+                delivered = false;
+            }
+            Err(err) => println!("Receive error: {:?}", err),
+        }
+    }
 }
 
-fn not_too_early(from: SystemTime) -> bool {
-    SystemTime::now().duration_since(from).unwrap().as_millis() > MIN_PAUSE
-}
+/// Argument which makes the bot apply database migrations and exit
+const MIGRATE_ONLY: &str = "--migrate-only";
 
 fn main() {
-    println!("Opening DB");
-    let mut db_path = env::var("CARGO_MANIFEST_DIR").unwrap_or(
-        env::current_dir()
-            .unwrap()
-            .into_os_string()
-            .into_string()
-            .unwrap_or(".".into()),
-    );
-    let mut en_db_path = db_path.clone();
-    db_path.push_str("/db/synsets_ua.db");
-    en_db_path.push_str("/db/synsets_en.db");
-    println!("DB path: {}", &db_path);
-    println!("En DB path: {}", &en_db_path);
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let migrate_only = args.iter().any(|arg| arg == MIGRATE_ONLY);
+    args.retain(|arg| arg != MIGRATE_ONLY);
+    let config = Config::load(&args).unwrap_or_else(|e| exit("Config", e));
 
-    // Verb selector
-    // Not empty `interpretation`
-    // Not enclosed in '()' `interpretation`
-    // Not starting with 'Те саме що' `interpretation`
-    const QUERY_UK: &str =
-        "SELECT id_syn, word, interpretation FROM wlist WHERE interpretation IS NOT NULL AND interpretation NOT LIKE '(%)' AND interpretation NOT LIKE 'Te саме%'";
-
-    // Not null definition
-    // Not starting with 'of ' `definition`
-    // Not starting with 'See ' `definition`
-    // Length of `definition` is longer than 5 chars
-    // `definition` does not contain `word` in it
-    const QUERY_EN: &str =
-        "SELECT word, definition, INSTR(definition, word) contains FROM words WHERE definition IS NOT NULL AND definition NOT LIKE 'of %' AND definition NOT LIKE 'See %' AND LENGTH(definition) > 5 AND contains = 0";
-
-    // Creates table `scores` with `user` and `score` rows if it does not yet exist
-    const SCORE_TABLE_CREATE: &str =
-        "CREATE TABLE IF NOT EXISTS scores (user INTEGER PRIMARY KEY UNIQUE, score INTEGER)";
+    println!("Opening DB");
+    println!("DB path: {}", config.uk_dictionary.display());
+    println!("En DB path: {}", config.en_dictionary.display());
+    println!("State DB path: {}", config.state_db.display());
+
+    // Scores of older versions are in the dictionaries or `score_db`
+    let legacy = [
+        config.score_db.as_path(),
+        &config.uk_dictionary,
+        &config.en_dictionary,
+    ];
+    let (state_db, applied) =
+        state::open(&config.state_db, &legacy).unwrap_or_else(|e| exit("State db", e));
+    for migration in applied {
+        println!(
+            "Applied migration {}: {}",
+            migration.version, migration.description
+        );
+    }
+    if migrate_only {
+        return;
+    }
 
     // Open db file
-    let db = sqlite::open(&db_path).expect("db expected");
-    // Create if not present `score` table
-    let _ = db.execute(SCORE_TABLE_CREATE).unwrap();
-    let mut data_uk: Vec<Question> = db
-        .prepare(QUERY_UK)
-        .unwrap()
-        .into_iter()
-        .map(|row| next_question(&row.unwrap()))
-        .collect();
+    let db = sqlite::open(&config.uk_dictionary).unwrap_or_else(|e| exit("Dictionary", e));
+    let data_uk = load_uk(&db).unwrap_or_else(|e| exit("Dictionary", e));
     println!("Loaded Ukrainian {} questions!", data_uk.len());
 
     // ENG db
-    let en_db = sqlite::open(&en_db_path).expect("En db expected");
-    let _ = en_db.execute(SCORE_TABLE_CREATE).unwrap();
-    let mut data_en: Vec<EnQuestion> = en_db
-        .prepare(QUERY_EN)
-        .unwrap()
-        .into_iter()
-        .map(|row| EnQuestion::new(&row.unwrap()))
-        .collect();
+    let en_db = sqlite::open(&config.en_dictionary).unwrap_or_else(|e| exit("En dictionary", e));
+    let data_en = load_en(&en_db).unwrap_or_else(|e| exit("En dictionary", e));
     println!("Loaded English {} questions!", data_en.len());
 
-    // RNG
-    let mut rng = thread_rng();
-    data_uk.shuffle(&mut rng);
-    data_en.shuffle(&mut rng);
-
-    // Log in to Discord using a bot token from the environment
-    let discord = Discord::from_bot_token(&env::var("DISCORD_TOKEN").expect("Expected token"))
-        .expect("login failed");
+    // Log in to Discord using a bot token from the environment,
+    // the gateway is read by its own thread with a client of its own
+    let token = env::var("DISCORD_TOKEN").expect("Expected token");
+    let discord = Discord::from_bot_token(&token).expect("login failed");
+    let gateway = Discord::from_bot_token(&token).expect("login failed");
 
     // Establish and use a websocket connection
-    let (mut connection, _) = discord.connect().expect("connect failed");
-    println!("Ready.");
-    let mut current_question = data_uk.choose(&mut rng).expect("no more uk questions");
-    let mut uk_asked: SystemTime = SystemTime::now();
-    let mut uk_hinted = false;
-    let mut current_en_question = data_en.choose(&mut rng).expect("no more eng questions");
-    let mut en_asked: SystemTime = SystemTime::now();
-    let mut en_hinted = false;
+    let mut backoff = Backoff::new(MIN_BACKOFF, MAX_BACKOFF);
+    let (connection, ready) = connect(&gateway, &mut backoff);
+    println!("Ready as {}.", ready.user.username);
+    let (sender, events) = mpsc::channel();
+    thread::spawn(move || read_gateway(gateway, connection, backoff, sender));
+    let mut transport = DiscordTransport { discord, events };
+    let mut game = Game::new(
+        &config,
+        UserId(ready.user.id.0),
+        &state_db,
+        &data_uk,
+        &data_en,
+    )
+    .unwrap_or_else(|e| exit("Game", e));
+    // channels carry on with the questions they had before the restart
+    if let Err(err) = game.restore(&transport) {
+        println!("Restore failed: {}", err);
+    }
 
+    // Wake up every `TICK` even without events, for hints and answers due by time
     loop {
-        match connection.recv_event() {
-            Ok(Event::ReactionAdd(reaction))
-                if reaction.emoji.eq(&ReactionEmoji::Unicode("❓".into())) =>
-            {
-                let channel = discord.get_channel(reaction.channel_id).unwrap();
-                let lang = match &channel {
-                    Channel::Public(c) => match &c.name {
-                        n if n.contains("uk") => Lang::Uk,
-                        n if n.contains("en") => Lang::En,
-                        _ => Lang::Uknown,
-                    },
-                    _ => Lang::Uknown,
-                };
-                let target = discord
-                    .get_message(reaction.channel_id, reaction.message_id)
-                    .unwrap();
-                if target.author.id.0 == 1165155849409405020 {
-                    match lang {
-                        Lang::Uk => {
-                            if !uk_hinted && not_too_early(uk_asked) {
-                                uk_hinted = true;
-                                uk_asked = SystemTime::now();
-                                drop(
-                                    discord
-                                        .send_message(
-                                            reaction.channel_id,
-                                            &produce_hint(current_question),
-                                            "",
-                                            false,
-                                        )
-                                        .unwrap(),
-                                );
-                            }
-                        }
-                        Lang::En => {
-                            if !en_hinted && not_too_early(en_asked) {
-                                en_hinted = true;
-                                en_asked = SystemTime::now();
-                                drop(
-                                    discord
-                                        .send_message(
-                                            reaction.channel_id,
-                                            &produce_hint(current_en_question),
-                                            "",
-                                            false,
-                                        )
-                                        .unwrap(),
-                                )
-                            }
-                        }
-                        _ => println!("Reaction to unknown channel: {:?}", channel),
-                    }
-                }
-            }
-            Ok(Event::MessageCreate(message)) => {
-                let text = message
-                    .content
-                    .to_owned()
-                    .trim()
-                    .replace(' ', "")
-                    .to_lowercase();
-                let channel = discord.get_channel(message.channel_id).unwrap();
-                let lang = match &channel {
-                    Channel::Public(c) => match &c.name {
-                        n if n.contains("uk") => Lang::Uk,
-                        n if n.contains("en") => Lang::En,
-                        _ => Lang::Uknown,
-                    },
-                    _ => Lang::Uknown,
-                };
-                // service commands
-                match lang {
-                    Lang::Uk => {
-                        if text.starts_with("!") {
-                            if text == "!next" || text == "!далі" || text == "!відповідь"
-                            {
-                                if uk_hinted || not_too_early(uk_asked) {
-                                    let _ = discord.send_message(
-                                        message.channel_id,
-                                        &current_question.answer,
-                                        "",
-                                        false,
-                                    );
-                                    // reset ask time and hint
-                                    uk_asked = SystemTime::now();
-                                    uk_hinted = false;
-                                    current_question =
-                                        data_uk.choose(&mut rng).expect("no more questions?");
-                                    let _ = discord.send_message(
-                                        message.channel_id,
-                                        &current_question.to_string(),
-                                        "",
-                                        false,
-                                    );
-                                } else {
-                                    react_timer(&message, &discord);
-                                }
-                            } else if text == "!q" || text == "!питання" || text == "!п" {
-                                let _ = discord.send_message(
-                                    message.channel_id,
-                                    &current_question.to_string(),
-                                    "",
-                                    false,
-                                );
-                            } else if text == "!підказка" || text == "!хінт" {
-                                if !uk_hinted {
-                                    if not_too_early(uk_asked) {
-                                        uk_hinted = true;
-                                        uk_asked = SystemTime::now();
-                                        let _ = discord.send_message(
-                                            message.channel_id,
-                                            &produce_hint(current_question),
-                                            "",
-                                            false,
-                                        );
-                                    } else {
-                                        react_timer(&message, &discord);
-                                    }
-                                } else {
-                                    react_stop(&message, &discord);
-                                }
-                            } else if text == "!рейтинг" {
-                                let (score, standing, total) = get_score(&db, message.author.id.0);
-                                if score == 0 {
-                                    let _ = discord.send_message(
-                                        message.channel_id,
-                                        &format!(
-                                            "{} нічого ще не відгадано...",
-                                            message.author.mention()
-                                        ),
-                                        "",
-                                        false,
-                                    );
-                                } else {
-                                    let _ = discord.send_message(
-                                        message.channel_id,
-                                        &format!(
-                                            "{} має {} очок і є {} зі {}",
-                                            message.author.mention(),
-                                            score,
-                                            standing,
-                                            total
-                                        ),
-                                        "",
-                                        false,
-                                    );
-                                }
-                            } else if text == "!топ" {
-                                let top = get_top(&db);
-                                let mut top_report = String::default();
-                                top.into_iter()
-                                    .enumerate()
-                                    .map(|(id, (user, score))| {
-                                        top_report.push_str(
-                                            format!(
-                                                "{}    |    {}    |    {}\n",
-                                                id + 1,
-                                                discord
-                                                    .get_user(discord::model::UserId(
-                                                        user.try_into().unwrap()
-                                                    ))
-                                                    .unwrap()
-                                                    .mention(),
-                                                score,
-                                            )
-                                            .as_str(),
-                                        );
-                                    })
-                                    .for_each(drop);
-                                let _ = discord.send_message(
-                                    message.channel_id,
-                                    &top_report,
-                                    "",
-                                    false,
-                                );
-                            } else if text == "!?" || text == "!help" {
-                                let _ = discord.send_message(
-                            message.channel_id,
-                            &format!("Відгадати слово за визначеням з тлумачного словника Української мови. Реєстр і навколишній текст не враховуються.\n\
-Рейтинг [вказаний в квадратних дужках після кожного питання] додається гравцю за вірну відповідь і є вищий у рідше вживаних слів.\n\
-**!?** | **!help** - інформація і команди;\n\
-**!next** | **!далі** | **!відповідь** - відповідь на поточне пиатння і нове питання;\n\
-**!q** | **!питання** | **!п** - повторити поточне питання;\n\
-**!підказка** | **!хінт** | реакція ❓ до питання - відобразити першу літеру відповіді;\n\
-**!топ** - відобразити топ 10 гравців з найвищим рейтингом;\n\
-**!рейтинг** - відобразити Ваш рейтинг;\n\
-Версія **{}**. Слів в словнику: **{}**", env!("CARGO_PKG_VERSION"), data_uk.len()),
-                            "",
-                            false,
-                        );
-                            }
-                        } else if text.contains(&current_question.answer) {
-                            // ansver verify and update score
-                            let new_score =
-                                increment_score(&db, message.author.id.0, current_question.score);
-                            let _ = discord.send_message(
-                                message.channel_id,
-                                format!(
-                                    "Вірно {}. Відповідь {}. Загальний рейтинг: {}",
-                                    message.author.mention(),
-                                    current_question.answer,
-                                    new_score
-                                )
-                                .as_str(),
-                                "",
-                                false,
-                            );
-                            // reset asked time and hinted
-                            uk_asked = SystemTime::now();
-                            uk_hinted = false;
-                            current_question = data_uk.choose(&mut rng).unwrap();
-                            let _ = discord.send_message(
-                                message.channel_id,
-                                &current_question.to_string(),
-                                "",
-                                false,
-                            );
-                        } else if !message.author.bot {
-                            let _ = discord.add_reaction(
-                                message.channel_id,
-                                message.id,
-                                ReactionEmoji::Unicode("➖".to_string()),
-                            );
-                        }
-                    }
-                    Lang::En => {
-                        if text.starts_with('!') {
-                            if text == "!next" || text == "!answer" {
-                                if en_hinted || not_too_early(en_asked) {
-                                    let _ = discord.send_message(
-                                        message.channel_id,
-                                        &current_en_question.answer,
-                                        "",
-                                        false,
-                                    );
-                                    en_asked = SystemTime::now();
-                                    en_hinted = false;
-                                    current_en_question =
-                                        data_en.choose(&mut rng).expect("no more questions?");
-                                    let _ = discord.send_message(
-                                        message.channel_id,
-                                        &current_en_question.to_string(),
-                                        "",
-                                        false,
-                                    );
-                                } else {
-                                    react_timer(&message, &discord);
-                                }
-                            } else if text == "!q" || text == "!question" {
-                                let _ = discord.send_message(
-                                    message.channel_id,
-                                    &current_en_question.to_string(),
-                                    "",
-                                    false,
-                                );
-                            } else if text == "!hint" {
-                                if !en_hinted {
-                                    if not_too_early(en_asked) {
-                                        en_hinted = true;
-                                        en_asked = SystemTime::now();
-                                        let _ = discord.send_message(
-                                            message.channel_id,
-                                            &produce_hint(current_en_question),
-                                            "",
-                                            false,
-                                        );
-                                    } else {
-                                        react_timer(&message, &discord);
-                                    }
-                                } else {
-                                    react_stop(&message, &discord);
-                                }
-                            } else if text == "!score" {
-                                let (score, standing, total) = get_score(&db, message.author.id.0);
-                                if score == 0 {
-                                    let _ = discord.send_message(
-                                        message.channel_id,
-                                        &format!(
-                                            "{} has not scored yet...",
-                                            message.author.mention()
-                                        ),
-                                        "",
-                                        false,
-                                    );
-                                } else {
-                                    let _ = discord.send_message(
-                                        message.channel_id,
-                                        &format!(
-                                            "{} have {} point and is {} out of {}",
-                                            message.author.mention(),
-                                            score,
-                                            standing,
-                                            total
-                                        ),
-                                        "",
-                                        false,
-                                    );
-                                }
-                            } else if text == "!top" {
-                                let top = get_top(&db);
-                                let mut top_report = String::default();
-                                top.into_iter()
-                                    .enumerate()
-                                    .map(|(id, (user, score))| {
-                                        top_report.push_str(
-                                            format!(
-                                                "{}    |    {}    |    {}\n",
-                                                id + 1,
-                                                discord
-                                                    .get_user(discord::model::UserId(
-                                                        user.try_into().unwrap()
-                                                    ))
-                                                    .unwrap()
-                                                    .mention(),
-                                                score,
-                                            )
-                                            .as_str(),
-                                        );
-                                    })
-                                    .for_each(drop);
-                                let _ = discord.send_message(
-                                    message.channel_id,
-                                    &top_report,
-                                    "",
-                                    false,
-                                );
-                            } else if text == "!?" || text == "!help" {
-                                let _ = discord.send_message(
-                            message.channel_id,
-                            &format!("Guess the word by it's definition. Answer must include exact word. Register and surrounding text are ignored.\n\
-Each question have a score [in square braces], which on correct answer is added to first player's tally.\n\
-**!?** | **!help** - info and commands;\n\
-**!next** | **!answer** - shows answer to current question and provides a new one;\n\
-**!q** | **!question** - repeat current question;\n\
-**!hint** | react ❓ under the question - produces hint with first and last letters of the answer word;\n\
-**!top** - top 10 score standings;\n\
-**!score** - display Your score;\n\
-Version **{}**. Total words count: **{}**", env!("CARGO_PKG_VERSION"), data_en.len()),
-                            "",
-                            false,
-                        );
-                            }
-                        } else if text.contains(&current_en_question.answer) {
-                            // ansver verify and update score
-                            let new_score = increment_score(
-                                &db,
-                                message.author.id.0,
-                                current_en_question.score,
-                            );
-                            // reset asked and hinted
-                            en_asked = SystemTime::now();
-                            en_hinted = false;
-                            let _ = discord.send_message(
-                                message.channel_id,
-                                format!(
-                                    "Correct {}. Answer is **{}**. Your total score: {}",
-                                    message.author.mention(),
-                                    current_en_question.answer,
-                                    new_score
-                                )
-                                .as_str(),
-                                "",
-                                false,
-                            );
-                            current_en_question = data_en.choose(&mut rng).unwrap();
-                            let _ = discord.send_message(
-                                message.channel_id,
-                                &current_en_question.to_string(),
-                                "",
-                                false,
-                            );
-                        } else if !message.author.bot {
-                            let _ = discord.add_reaction(
-                                message.channel_id,
-                                message.id,
-                                ReactionEmoji::Unicode("➖".to_string()),
-                            );
-                        }
-                    }
-                    Lang::Uknown => println!("Unknown channel message {:?}", channel),
+        match transport.recv_event(TICK) {
+            Ok(Some(event)) => {
+                // a failed event must not take the other channels down with it
+                if let Err(err) = game.handle_event(&transport, event) {
+                    println!("Event failed: {}", err);
                 }
             }
-            Ok(_) => {}
-            Err(discord::Error::Closed(code, body)) => {
-                println!("Gateway closed on us with code {:?}: {}", code, body);
-                break;
-            }
-            Err(err) => println!("Receive error: {:?}", err),
+            Ok(None) => {}
+            Err(err) => exit("Gateway", err),
+        }
+        if let Err(err) = game.tick(&transport) {
+            println!("Timer failed: {}", err);
         }
     }
 }
-
-#[test]
-fn bold_test() {
-    let r = Regex::new(r"(\[B\])|(\[\/B])").unwrap();
-    let res = r.replace_all("Те саме, що [B]заванта́жувати[/B]", "**");
-    assert_eq!(res, "Те саме, що **заванта́жувати**")
-}
-
-#[test]
-fn not_yet_test() {
-    let one_min_in_the_past = SystemTime::UNIX_EPOCH
-        + SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .saturating_sub(std::time::Duration::from_secs(61));
-    assert!(not_too_early(one_min_in_the_past));
-    assert!(!not_too_early(SystemTime::now()));
-}
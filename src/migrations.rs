@@ -0,0 +1,142 @@
+use crate::state::transaction;
+use sqlite::{Connection, Result};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Change of the state database schema
+pub struct Migration {
+    pub version: i64,
+    pub description: &'static str,
+    sql: &'static str,
+}
+
+/// Schema changes in the order they are applied. Applied ones must never change,
+/// new tables and columns come with a new migration at the end.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "scores and channel bindings",
+        sql: "CREATE TABLE IF NOT EXISTS scores (user INTEGER PRIMARY KEY UNIQUE, score INTEGER);
+              CREATE TABLE IF NOT EXISTS channels (channel INTEGER PRIMARY KEY UNIQUE, lang TEXT);",
+    },
+    Migration {
+        version: 2,
+        description: "game states, decks and sessions of channels",
+        sql: "CREATE TABLE IF NOT EXISTS games (channel INTEGER PRIMARY KEY UNIQUE, state TEXT);
+              CREATE TABLE IF NOT EXISTS decks (channel INTEGER, lang TEXT, seed INTEGER,
+                  position INTEGER, PRIMARY KEY (channel, lang));
+              CREATE TABLE IF NOT EXISTS sessions (channel INTEGER, lang TEXT, question INTEGER,
+                  asked INTEGER, hints INTEGER, message INTEGER, active INTEGER NOT NULL,
+                  guessed INTEGER NOT NULL, PRIMARY KEY (channel, lang));",
+    },
+];
+
+// Creates table `schema_version` with `version` of each applied migration and when it was
+const VERSION_TABLE_CREATE: &str =
+    "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER PRIMARY KEY, applied INTEGER)";
+
+/// Version of the last migration applied to the `db`, 0 if there was none
+pub fn version(db: &Connection) -> Result<i64> {
+    db.execute(VERSION_TABLE_CREATE)?;
+    let mut version = 0;
+    for row in db.prepare("SELECT MAX(version) AS version FROM schema_version")? {
+        version = row?.try_read::<Option<i64>, _>("version")?.unwrap_or(0);
+    }
+    Ok(version)
+}
+
+/// Applies migrations the `db` does not have yet, each one entirely or not at all
+pub fn migrate(db: &Connection) -> Result<Vec<&'static Migration>> {
+    apply_all(db, MIGRATIONS)
+}
+
+/// Applies migrations the `db` does not have yet as a part of the transaction it is in
+pub fn migrate_in_transaction(db: &Connection) -> Result<Vec<&'static Migration>> {
+    let pending = pending(db, MIGRATIONS)?;
+    for migration in &pending {
+        apply(db, migration)?;
+    }
+    Ok(pending)
+}
+
+fn apply_all(db: &Connection, migrations: &'static [Migration]) -> Result<Vec<&'static Migration>> {
+    let mut applied = vec![];
+    for migration in pending(db, migrations)? {
+        transaction(db, "BEGIN", || apply(db, migration))?;
+        applied.push(migration);
+    }
+    Ok(applied)
+}
+
+/// Those of the `migrations` the `db` does not have yet
+fn pending(db: &Connection, migrations: &'static [Migration]) -> Result<Vec<&'static Migration>> {
+    let current = version(db)?;
+    Ok(migrations.iter().filter(|m| m.version > current).collect())
+}
+
+fn apply(db: &Connection, migration: &Migration) -> Result<()> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    db.execute(migration.sql)?;
+    db.execute(format!(
+        "INSERT INTO schema_version (version, applied) VALUES ({}, {now})",
+        migration.version
+    ))
+}
+
+#[test]
+fn versions_test() {
+    for (i, migration) in MIGRATIONS.iter().enumerate() {
+        assert_eq!(migration.version, i as i64 + 1);
+    }
+}
+
+#[test]
+fn migrate_test() {
+    let db = sqlite::open(":memory:").unwrap();
+    assert_eq!(version(&db).unwrap(), 0);
+    assert_eq!(migrate(&db).unwrap().len(), MIGRATIONS.len());
+    assert_eq!(version(&db).unwrap(), MIGRATIONS.len() as i64);
+    assert!(migrate(&db).unwrap().is_empty());
+}
+
+#[test]
+fn legacy_fixture_test() {
+    use crate::score::ScoreStore;
+    let db = sqlite::open(":memory:").unwrap();
+    db.execute(include_str!("../tests/fixtures/legacy.sql"))
+        .unwrap();
+    let applied = migrate(&db).unwrap();
+    assert_eq!(applied[0].version, 1);
+    assert_eq!(version(&db).unwrap(), MIGRATIONS.len() as i64);
+    assert_eq!(db.get_score(241977946434076672).unwrap(), (42, 1, 2));
+    assert!(crate::channels::get_bindings(&db).unwrap().is_empty());
+    assert!(crate::channels::get_states(&db).unwrap().is_empty());
+    // the dictionary is left as it was
+    assert_eq!(crate::question::load_uk(&db).unwrap().len(), 2);
+}
+
+#[test]
+fn failed_migration_test() {
+    const BROKEN: &[Migration] = &[
+        Migration {
+            version: 1,
+            description: "fine",
+            sql: "CREATE TABLE one (id INTEGER)",
+        },
+        Migration {
+            version: 2,
+            description: "fails halfway",
+            sql: "CREATE TABLE two (id INTEGER); CREATE TABLE one (id INTEGER)",
+        },
+    ];
+    let db = sqlite::open(":memory:").unwrap();
+    assert!(apply_all(&db, BROKEN).is_err());
+    assert_eq!(version(&db).unwrap(), 1);
+    let mut tables = db
+        .prepare("SELECT name FROM sqlite_master WHERE name == 'two'")
+        .unwrap()
+        .into_iter();
+    assert!(tables.next().is_none());
+}
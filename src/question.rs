@@ -0,0 +1,441 @@
+use crate::answer::normalize;
+use rand::{rngs::StdRng, seq::SliceRandom, thread_rng, Rng, SeedableRng};
+use regex::Regex;
+use sqlite::{Connection, Row};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+};
+use unicode_normalization::char::is_combining_mark;
+
+// Verb selector
+// Not empty `interpretation`
+// Not enclosed in '()' `interpretation`
+// Not starting with 'Те саме що' `interpretation`
+const QUERY_UK: &str =
+    "SELECT rowid AS source_id, id_syn, word, interpretation FROM wlist WHERE interpretation IS NOT NULL AND interpretation NOT LIKE '(%)' AND interpretation NOT LIKE 'Te саме%' ORDER BY rowid";
+
+// Not null definition
+// Not starting with 'of ' `definition`
+// Not starting with 'See ' `definition`
+// Length of `definition` is longer than 5 chars
+// `definition` does not contain `word` in it
+const QUERY_EN: &str =
+    "SELECT rowid AS source_id, word, definition, INSTR(definition, word) contains FROM words WHERE definition IS NOT NULL AND definition NOT LIKE 'of %' AND definition NOT LIKE 'See %' AND LENGTH(definition) > 5 AND contains = 0 ORDER BY rowid";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Lang {
+    Uk,
+    En,
+    Uknown,
+}
+
+impl Lang {
+    /// Language by its code, as used in config and commands
+    pub fn from_code(code: &str) -> Option<Lang> {
+        match code {
+            "uk" => Some(Lang::Uk),
+            "en" => Some(Lang::En),
+            _ => None,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Lang::Uk => "uk",
+            Lang::En => "en",
+            Lang::Uknown => "",
+        }
+    }
+}
+
+/// Question of any language the game can be played with, displayed as it is asked
+pub trait IsQuestion: Display {
+    /// Language of the dictionary the question comes from
+    const LANG: Lang;
+
+    fn get_answer(&self) -> &str;
+    /// Points for the correct answer
+    fn score(&self) -> i64;
+    /// Row of the question in its dictionary
+    fn source_id(&self) -> i64;
+
+    /// Another word with the same meaning, if the dictionary knows one
+    fn synonym(&self) -> Option<&str> {
+        None
+    }
+
+    /// Hint of the `level`, starting from 1, or `None` if there are no more
+    fn hint(&self, level: usize) -> Option<String> {
+        produce_hint(self, level)
+    }
+}
+
+pub struct Question {
+    pub id: i64,
+    pub question: String,
+    pub answer: String,
+    pub score: i64,
+    /// Id of the synset, the group of words with the same meaning, the answer is in
+    pub synset: i64,
+    /// Other words of the same synset
+    pub synonyms: Vec<String>,
+    bold: Regex,
+}
+
+impl Question {
+    pub fn new(question: String, answer: String, score: i64) -> Self {
+        Question {
+            id: 0,
+            question,
+            answer,
+            score,
+            synset: 0,
+            synonyms: vec![],
+            bold: Regex::new(r"(\[B\])|(\[\/B])").unwrap(),
+        }
+    }
+}
+
+impl Display for Question {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!(
+            "**{}** ({} літер) [+{}]",
+            self.bold.replace_all(&self.question, ""),
+            normalize(&self.answer).chars().count(),
+            self.score
+        ))
+    }
+}
+
+impl IsQuestion for Question {
+    const LANG: Lang = Lang::Uk;
+
+    fn get_answer(&self) -> &str {
+        &self.answer
+    }
+
+    fn score(&self) -> i64 {
+        self.score
+    }
+
+    fn source_id(&self) -> i64 {
+        self.id
+    }
+
+    fn synonym(&self) -> Option<&str> {
+        self.synonyms.first().map(String::as_str)
+    }
+}
+
+pub struct EnQuestion {
+    pub id: i64,
+    pub question: String,
+    pub answer: String,
+    pub score: i64,
+}
+
+impl EnQuestion {
+    pub fn new(r: &Row) -> Self {
+        let mut rng = thread_rng();
+        let mut question: String = r
+            .read::<&str, _>("definition")
+            .chars()
+            .filter(|c| c.is_alphanumeric() || c.eq(&' '))
+            .collect();
+        question = question
+            .split(' ')
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        if question.contains('.') {
+            question = question.split_once('.').unwrap().0.to_string() // first part before '.'
+        }
+        let answer = r.read::<&str, _>("word").to_string().to_lowercase();
+        let score = rng.gen_range(1..5);
+        EnQuestion {
+            id: r.read::<i64, _>("source_id"),
+            question,
+            answer,
+            score,
+        }
+    }
+}
+
+impl Display for EnQuestion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!(
+            "**{}** ({} letters) [{} point(s)]",
+            self.question,
+            self.answer.chars().count(),
+            self.score
+        ))
+    }
+}
+
+impl IsQuestion for EnQuestion {
+    const LANG: Lang = Lang::En;
+
+    fn get_answer(&self) -> &str {
+        &self.answer
+    }
+
+    fn score(&self) -> i64 {
+        self.score
+    }
+
+    fn source_id(&self) -> i64 {
+        self.id
+    }
+}
+
+pub fn next_question(r: &Row) -> Question {
+    let new_answer = r.read::<&str, _>("word").replace('\"', "");
+    Question {
+        id: r.read::<i64, _>("source_id"),
+        synset: r.read::<i64, _>("id_syn"),
+        ..Question::new(
+            r.read::<&str, _>("interpretation").to_string(),
+            new_answer,
+            r.read::<i64, _>("id_syn"),
+        )
+    }
+}
+
+/// Loads all Ukrainian questions from `wlist` table of the dictionary,
+/// with synonyms out of the same synsets
+pub fn load_uk(db: &Connection) -> sqlite::Result<Vec<Question>> {
+    let mut questions = db
+        .prepare(QUERY_UK)?
+        .into_iter()
+        .map(|row| Ok(next_question(&row?)))
+        .collect::<sqlite::Result<Vec<_>>>()?;
+    let mut synsets: HashMap<i64, Vec<String>> = HashMap::new();
+    for q in &questions {
+        synsets.entry(q.synset).or_default().push(q.answer.clone());
+    }
+    for q in &mut questions {
+        // words differing only in stress or case are the same answer
+        let mut seen = HashSet::from([normalize(&q.answer)]);
+        q.synonyms = synsets[&q.synset]
+            .iter()
+            .filter(|word| seen.insert(normalize(word)))
+            .cloned()
+            .collect();
+    }
+    Ok(questions)
+}
+
+/// Loads all English questions from `words` table of the dictionary
+pub fn load_en(db: &Connection) -> sqlite::Result<Vec<EnQuestion>> {
+    db.prepare(QUERY_EN)?
+        .into_iter()
+        .map(|row| Ok(EnQuestion::new(&row?)))
+        .collect()
+}
+
+/// Apostrophe variants used inside Ukrainian words
+pub const APOSTROPHES: [char; 3] = ['\'', '’', 'ʼ'];
+
+/// Pieces of the `answer` as hints show them: letters along with their combining marks,
+/// which hints mask, and separators between words like spaces, hyphens and apostrophes,
+/// which they always show
+fn hint_units(answer: &str) -> Vec<(String, bool)> {
+    let mut units: Vec<(String, bool)> = vec![];
+    for c in answer.chars() {
+        match units.last_mut() {
+            Some((unit, true)) if is_combining_mark(c) => unit.push(c),
+            _ => units.push((c.to_string(), c.is_alphanumeric())),
+        }
+    }
+    units
+}
+
+/// Hints of growing `level` for the question:
+/// 1. length of the answer
+/// 2. first and last letters of its words, only the first one of two letter words
+/// 3. one more random letter each level, until half of the letters are shown
+/// 4. a synonym, if there is one
+pub fn produce_hint<T>(q: &T, level: usize) -> Option<String>
+where
+    T: IsQuestion + ?Sized,
+{
+    // stress is only shown when the answer is revealed
+    let units = hint_units(&normalize(q.get_answer()));
+    let letters: Vec<usize> = (0..units.len()).filter(|i| units[*i].1).collect();
+    let half = letters.len() / 2;
+    // letters of each word, apostrophes do not split words
+    let mut words: Vec<Vec<usize>> = vec![vec![]];
+    for (i, (unit, letter)) in units.iter().enumerate() {
+        match words.last_mut() {
+            Some(word) if *letter => word.push(i),
+            Some(word) if !word.is_empty() && !unit.starts_with(APOSTROPHES) => words.push(vec![]),
+            _ => {}
+        }
+    }
+    let mut shown = vec![];
+    for word in &words {
+        match word.len() {
+            0 | 1 => {}
+            2 => shown.push(word[0]),
+            n => shown.extend([word[0], word[n - 1]]),
+        }
+    }
+    // the same letters are shown each time the question is hinted
+    let mut rest: Vec<usize> = letters.into_iter().filter(|i| !shown.contains(i)).collect();
+    rest.shuffle(&mut StdRng::seed_from_u64(q.source_id() as u64));
+    rest.truncate(half.saturating_sub(shown.len()));
+
+    let mut stages = vec![vec![]];
+    if !shown.is_empty() {
+        stages.push(shown.clone());
+    }
+    for i in rest {
+        shown.push(i);
+        stages.push(shown.clone());
+    }
+    match level {
+        0 => None,
+        n if n <= stages.len() => Some(
+            units
+                .iter()
+                .enumerate()
+                .map(|(i, (unit, letter))| {
+                    if !letter || stages[n - 1].contains(&i) {
+                        unit.as_str()
+                    } else {
+                        "◾"
+                    }
+                })
+                .collect(),
+        ),
+        n if n == stages.len() + 1 => q.synonym().map(|s| format!("≈ {}", s)),
+        _ => None,
+    }
+}
+
+#[test]
+fn bold_test() {
+    let r = Regex::new(r"(\[B\])|(\[\/B])").unwrap();
+    let res = r.replace_all("Те саме, що [B]заванта́жувати[/B]", "**");
+    assert_eq!(res, "Те саме, що **заванта́жувати**")
+}
+
+#[test]
+fn load_test() {
+    let db = sqlite::open(":memory:").unwrap();
+    db.execute(
+        "CREATE TABLE wlist (id_syn INTEGER, word TEXT, interpretation TEXT);
+         INSERT INTO wlist VALUES (7, 'кіт', 'Свійська тварина'), (8, 'пес', NULL), (7, 'котик', 'Кіт'),
+             (7, 'кі́т', 'Кицька'), (7, 'ко́тик', 'Кошеня');
+         CREATE TABLE words (word TEXT, definition TEXT);
+         INSERT INTO words VALUES ('of', 'of something'), ('Cat', 'A small. Domestic animal');",
+    )
+    .unwrap();
+    let uk = load_uk(&db).unwrap();
+    assert_eq!(uk.len(), 4);
+    assert_eq!(
+        (uk[0].source_id(), uk[0].score(), uk[0].get_answer()),
+        (1, 7, "кіт")
+    );
+    assert_eq!((uk[0].synset, uk[1].synset), (7, 7));
+    assert_eq!(uk[0].synonyms, ["котик"]);
+    assert_eq!(uk[1].synonyms, ["кіт"]);
+    assert_eq!(uk[2].synonyms, ["котик"]);
+    let en = load_en(&db).unwrap();
+    assert_eq!(en.len(), 1);
+    assert_eq!((en[0].source_id(), en[0].get_answer()), (2, "cat"));
+    assert_eq!(en[0].question, "A small Domestic animal");
+}
+
+#[test]
+fn hint_levels_test() {
+    let mut q = Question::new("Свійська тварина".into(), "кішечка".into(), 3);
+    q.synonyms = vec!["кицька".into()];
+    let hints: Vec<String> = (1..).map_while(|level| q.hint(level)).collect();
+    assert_eq!(hints[0], "◾◾◾◾◾◾◾");
+    assert_eq!(hints[1], "к◾◾◾◾◾а");
+    assert_eq!(hints[2].chars().filter(|c| *c != '◾').count(), 3);
+    assert!(hints[2].starts_with('к') && hints[2].ends_with('а'));
+    assert_eq!(hints[3], "≈ кицька");
+    assert_eq!(hints.len(), 4);
+    assert_eq!(q.hint(3), Some(hints[2].clone()));
+    q.synonyms.clear();
+    assert_eq!(q.hint(4), None);
+}
+
+/// Checks that hints of `q` end, only ever reveal more letters, keep something hidden
+/// and show the separators of the answer
+#[cfg(test)]
+fn check_hints<Q: IsQuestion>(q: &Q) {
+    let answer = normalize(q.get_answer());
+    let units = hint_units(&answer);
+    let letters = units.iter().filter(|u| u.1).count();
+    let mut hidden = usize::MAX;
+    for level in 1.. {
+        let Some(hint) = q.hint(level) else { break };
+        assert!(level <= letters + 3, "{answer:?} has too many hints");
+        if hint.starts_with('≈') {
+            continue;
+        }
+        let mut rest = hint.as_str();
+        for (unit, letter) in &units {
+            rest = match rest.strip_prefix(unit.as_str()) {
+                Some(rest) => rest,
+                None if *letter => rest
+                    .strip_prefix('◾')
+                    .unwrap_or_else(|| panic!("{hint:?} does not match {answer:?}")),
+                None => panic!("{hint:?} lost separator {unit:?} of {answer:?}"),
+            };
+        }
+        assert!(rest.is_empty(), "{hint:?} is longer than {answer:?}");
+        let masked = hint.matches('◾').count();
+        assert!(
+            masked <= hidden,
+            "{hint:?} hides more than the previous one"
+        );
+        assert!(letters == 0 || masked > 0, "{hint:?} gives {answer:?} away");
+        hidden = masked;
+    }
+}
+
+#[test]
+fn short_and_multi_part_hints_test() {
+    let hint = |answer: &str, level| Question::new(String::new(), answer.into(), 1).hint(level);
+    assert_eq!(hint("я", 1), Some("◾".into()));
+    assert_eq!(hint("я", 2), None);
+    assert_eq!(hint("ні", 2), Some("н◾".into()));
+    assert_eq!(hint("", 1), Some("".into()));
+    assert_eq!(hint("пів-яблука", 1), Some("◾◾◾-◾◾◾◾◾◾".into()));
+    assert_eq!(hint("пів-яблука", 2), Some("п◾в-я◾◾◾◾а".into()));
+    assert_eq!(hint("м'ята", 2), Some("м'◾◾а".into()));
+    assert_eq!(hint("заванта\u{301}жувати", 1), Some("◾".repeat(13)));
+    assert_eq!(hint("сі\u{301}м", 2), Some("с◾м".into()));
+}
+
+#[test]
+fn dictionary_hints_test() {
+    // dictionaries are not a part of the repository, check them where they are present
+    let config = crate::config::Config::default();
+    if config.uk_dictionary.is_file() {
+        let db = sqlite::open(&config.uk_dictionary).unwrap();
+        load_uk(&db).unwrap().iter().for_each(check_hints);
+    }
+    if config.en_dictionary.is_file() {
+        let db = sqlite::open(&config.en_dictionary).unwrap();
+        load_en(&db).unwrap().iter().for_each(check_hints);
+    }
+}
+
+#[cfg(test)]
+proptest::proptest! {
+    #[test]
+    fn hints_property_test(
+        answer in "([абвгґіїєкя]\u{301}?|[ '’ʼ-]|[a-z]){0,16}",
+        id in proptest::num::i64::ANY,
+    ) {
+        let mut q = Question::new(String::new(), answer, 1);
+        q.id = id;
+        check_hints(&q);
+    }
+}
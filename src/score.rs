@@ -0,0 +1,142 @@
+use crate::{error::Result, state::transaction};
+use sqlite::{Connection, State};
+use std::{cell::RefCell, collections::HashMap};
+
+/// Where players' scores are kept
+pub trait ScoreStore {
+    /// Score of the `user`, their standing and the number of players, zeros if they have none
+    fn get_score(&self, user: u64) -> Result<(i64, usize, usize)>;
+
+    /// Top 10 players with their scores, best first
+    fn get_top(&self) -> Result<Vec<(u64, i64)>>;
+
+    /// Adds `score` to the total of the `user` and returns the new total
+    fn increment_score(&self, user: u64, score: i64) -> Result<i64>;
+}
+
+/// Scores in the `scores` table
+impl ScoreStore for Connection {
+    fn get_score(&self, user: u64) -> Result<(i64, usize, usize)> {
+        let mut statement = self.prepare("SELECT score FROM scores WHERE user == :user")?;
+        statement.bind((":user", user as i64))?;
+        if statement.next()? == State::Done {
+            return Ok((0, 0, 0));
+        }
+        let score = statement.read::<i64, _>("score")?;
+        let mut statement = self.prepare(
+            "SELECT COUNT(*) AS total, COUNT(CASE WHEN score > :score THEN 1 END) AS better \
+             FROM scores",
+        )?;
+        statement.bind((":score", score))?;
+        statement.next()?;
+        let better = statement.read::<i64, _>("better")? as usize;
+        let total = statement.read::<i64, _>("total")? as usize;
+        Ok((score, better + 1, total))
+    }
+
+    fn get_top(&self) -> Result<Vec<(u64, i64)>> {
+        self.prepare("SELECT user, score FROM scores ORDER BY score DESC LIMIT 10")?
+            .into_iter()
+            .map(|row| {
+                let row = row?;
+                let user = row.try_read::<i64, _>("user")? as u64;
+                Ok((user, row.try_read::<i64, _>("score")?))
+            })
+            .collect()
+    }
+
+    fn increment_score(&self, user: u64, score: i64) -> Result<i64> {
+        // a failed increment leaves the score as it was
+        transaction(self, "BEGIN IMMEDIATE", || {
+            increment(self, user as i64, score)
+        })
+    }
+}
+
+fn increment(db: &Connection, user: i64, score: i64) -> Result<i64> {
+    let mut statement =
+        db.prepare("INSERT OR IGNORE INTO scores (user, score) VALUES (:user, 0)")?;
+    statement.bind((":user", user))?;
+    statement.next()?;
+    let mut statement =
+        db.prepare("UPDATE scores SET score = score + :score WHERE user == :user")?;
+    statement.bind(&[(":score", score), (":user", user)][..])?;
+    statement.next()?;
+    let mut statement = db.prepare("SELECT score FROM scores WHERE user == :user")?;
+    statement.bind((":user", user))?;
+    statement.next()?;
+    Ok(statement.read::<i64, _>("score")?)
+}
+
+/// Scores kept in memory only, for tests and trying the bot out
+#[derive(Default)]
+pub struct MemoryScores {
+    scores: RefCell<HashMap<u64, i64>>,
+}
+
+impl ScoreStore for MemoryScores {
+    fn get_score(&self, user: u64) -> Result<(i64, usize, usize)> {
+        let scores = self.scores.borrow();
+        Ok(match scores.get(&user) {
+            Some(score) => {
+                let better = scores.values().filter(|other| *other > score).count();
+                (*score, better + 1, scores.len())
+            }
+            None => (0, 0, 0),
+        })
+    }
+
+    fn get_top(&self) -> Result<Vec<(u64, i64)>> {
+        let mut top: Vec<(u64, i64)> = self
+            .scores
+            .borrow()
+            .iter()
+            .map(|(user, score)| (*user, *score))
+            .collect();
+        top.sort_by_key(|(user, score)| (-score, *user));
+        top.truncate(10);
+        Ok(top)
+    }
+
+    fn increment_score(&self, user: u64, score: i64) -> Result<i64> {
+        let mut scores = self.scores.borrow_mut();
+        let total = scores.entry(user).or_default();
+        *total += score;
+        Ok(*total)
+    }
+}
+
+#[cfg(test)]
+fn check_store(store: &dyn ScoreStore) {
+    assert_eq!(store.get_score(1).unwrap(), (0, 0, 0));
+    assert_eq!(store.increment_score(1, 3).unwrap(), 3);
+    assert_eq!(store.increment_score(1, 2).unwrap(), 5);
+    assert_eq!(store.increment_score(u64::MAX, 7).unwrap(), 7);
+    assert_eq!(store.increment_score(2, 5).unwrap(), 5);
+    assert_eq!(store.get_score(1).unwrap(), (5, 2, 3));
+    assert_eq!(store.get_score(2).unwrap(), (5, 2, 3));
+    assert_eq!(store.get_score(u64::MAX).unwrap(), (7, 1, 3));
+    let top = store.get_top().unwrap();
+    assert_eq!(top[0], (u64::MAX, 7));
+    assert_eq!(top.len(), 3);
+    for user in 10..20 {
+        store.increment_score(user, 1).unwrap();
+    }
+    assert_eq!(store.get_top().unwrap().len(), 10);
+}
+
+#[test]
+fn sqlite_scores_test() {
+    let db = sqlite::open(":memory:").unwrap();
+    crate::migrations::migrate(&db).unwrap();
+    check_store(&db);
+    db.execute("DROP TABLE scores").unwrap();
+    assert!(db.increment_score(1, 1).is_err());
+    // the failed increment did not leave a transaction open
+    db.execute("BEGIN").unwrap();
+}
+
+#[test]
+fn memory_scores_test() {
+    check_store(&MemoryScores::default());
+}
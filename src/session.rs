@@ -0,0 +1,221 @@
+use crate::{
+    question::{IsQuestion, Lang},
+    transport::{ChannelId, MessageId},
+};
+use sqlite::{Connection, Result};
+use std::{
+    collections::HashMap,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// State of the game in a single channel
+pub struct GameSession<'a, Q> {
+    pub question: &'a Q,
+    pub asked: SystemTime,
+    // hints given for the current question
+    pub hints: usize,
+    // id of the message the question was posted with, if known
+    pub message: Option<MessageId>,
+    // somebody played since the bot moved on or paused by itself, so it should keep hinting
+    pub active: bool,
+    // last guess at any question, or when the session became active
+    pub guessed: SystemTime,
+}
+
+impl<'a, Q> GameSession<'a, Q> {
+    pub fn new(question: &'a Q) -> Self {
+        GameSession {
+            question,
+            asked: SystemTime::now(),
+            hints: 0,
+            message: None,
+            active: true,
+            guessed: SystemTime::now(),
+        }
+    }
+
+    /// Replaces current question and resets ask time and hints
+    pub fn next(&mut self, question: &'a Q) {
+        self.question = question;
+        self.asked = SystemTime::now();
+        self.hints = 0;
+        self.message = None;
+    }
+
+    /// Marks the session as played in, counting from now if it was not
+    pub fn activate(&mut self) {
+        if !self.active {
+            self.active = true;
+            self.guessed = SystemTime::now();
+        }
+    }
+
+    /// Counts a hint for the current question and resets ask time
+    pub fn hint(&mut self) {
+        self.hints += 1;
+        self.asked = SystemTime::now();
+    }
+}
+
+impl<'a, Q: IsQuestion> GameSession<'a, Q> {
+    /// What is saved of the session to carry it on after a restart
+    pub fn record(&self) -> SessionRecord {
+        let secs = |time: SystemTime| {
+            time.duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs()
+        };
+        SessionRecord {
+            question: self.question.source_id(),
+            asked: secs(self.asked),
+            hints: self.hints,
+            message: self.message,
+            active: self.active,
+            guessed: secs(self.guessed),
+        }
+    }
+
+    /// Session with the `question` carried on from the `record`
+    pub fn restore(question: &'a Q, record: &SessionRecord) -> Self {
+        let mut session = GameSession::new(question);
+        session.asked = UNIX_EPOCH + Duration::from_secs(record.asked);
+        session.hints = record.hints;
+        session.message = record.message;
+        // a session which went quiet stays so until somebody writes
+        session.active = record.active;
+        session.guessed = UNIX_EPOCH + Duration::from_secs(record.guessed);
+        session
+    }
+}
+
+/// Session as it is saved in the database
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SessionRecord {
+    /// Source id of the question
+    pub question: i64,
+    /// Seconds since Unix epoch
+    pub asked: u64,
+    pub hints: usize,
+    pub message: Option<MessageId>,
+    pub active: bool,
+    /// Seconds since Unix epoch
+    pub guessed: u64,
+}
+
+/// Saved sessions of the `lang` game
+pub fn get_sessions(db: &Connection, lang: Lang) -> Result<HashMap<ChannelId, SessionRecord>> {
+    let mut sessions = HashMap::new();
+    let mut statement = db.prepare("SELECT * FROM sessions WHERE lang == :lang")?;
+    statement.bind((":lang", lang.code()))?;
+    for row in statement.into_iter() {
+        let row = row?;
+        let channel = ChannelId(row.try_read::<i64, _>("channel")? as u64);
+        let record = SessionRecord {
+            question: row.try_read::<i64, _>("question")?,
+            asked: row.try_read::<i64, _>("asked")? as u64,
+            hints: row.try_read::<i64, _>("hints")? as usize,
+            message: row
+                .try_read::<Option<i64>, _>("message")?
+                .map(|id| MessageId(id as u64)),
+            active: row.try_read::<i64, _>("active")? != 0,
+            guessed: row.try_read::<i64, _>("guessed")? as u64,
+        };
+        sessions.insert(channel, record);
+    }
+    Ok(sessions)
+}
+
+pub fn save_session(
+    db: &Connection,
+    channel: ChannelId,
+    lang: Lang,
+    record: &SessionRecord,
+) -> Result<()> {
+    let mut statement = db.prepare(
+        "INSERT OR REPLACE INTO sessions \
+         (channel, lang, question, asked, hints, message, active, guessed) \
+         VALUES (:channel, :lang, :question, :asked, :hints, :message, :active, :guessed)",
+    )?;
+    statement.bind(
+        &[
+            (":channel", channel.0 as i64),
+            (":question", record.question),
+            (":asked", record.asked as i64),
+            (":hints", record.hints as i64),
+            (":active", i64::from(record.active)),
+            (":guessed", record.guessed as i64),
+        ][..],
+    )?;
+    statement.bind((":lang", lang.code()))?;
+    statement.bind((":message", record.message.map(|message| message.0 as i64)))?;
+    statement.next()?;
+    Ok(())
+}
+
+pub fn delete_session(db: &Connection, channel: ChannelId, lang: Lang) -> Result<()> {
+    let mut statement =
+        db.prepare("DELETE FROM sessions WHERE channel == :channel AND lang == :lang")?;
+    statement.bind((":channel", channel.0 as i64))?;
+    statement.bind((":lang", lang.code()))?;
+    statement.next()?;
+    Ok(())
+}
+
+/// Whether more than `pause` has passed `from` the given time
+pub fn not_too_early(from: SystemTime, pause: Duration) -> bool {
+    // clock going backwards counts as no time passed
+    SystemTime::now().duration_since(from).unwrap_or_default() > pause
+}
+
+#[test]
+fn not_yet_test() {
+    let one_min_in_the_past = SystemTime::UNIX_EPOCH
+        + SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .saturating_sub(std::time::Duration::from_secs(61));
+    let pause = Duration::from_secs(60);
+    assert!(not_too_early(one_min_in_the_past, pause));
+    assert!(!not_too_early(SystemTime::now(), pause));
+}
+
+#[test]
+fn session_next_test() {
+    let first = "first";
+    let second = "second";
+    let mut session = GameSession::new(&first);
+    session.hint();
+    session.message = Some(MessageId(1));
+    session.next(&second);
+    assert_eq!(*session.question, "second");
+    assert_eq!(session.hints, 0);
+    assert!(session.message.is_none());
+}
+
+#[test]
+fn sessions_test() {
+    let db = sqlite::open(":memory:").unwrap();
+    crate::migrations::migrate(&db).unwrap();
+    let question = crate::question::Question::new("Свійська тварина".into(), "кіт".into(), 3);
+    let mut session = GameSession::new(&question);
+    session.hint();
+    session.message = Some(MessageId(u64::MAX));
+    save_session(&db, ChannelId(1), Lang::Uk, &session.record()).unwrap();
+    session.message = None;
+    save_session(&db, ChannelId(2), Lang::Uk, &session.record()).unwrap();
+    save_session(&db, ChannelId(2), Lang::En, &session.record()).unwrap();
+    delete_session(&db, ChannelId(2), Lang::En).unwrap();
+    let saved = get_sessions(&db, Lang::Uk).unwrap();
+    assert!(get_sessions(&db, Lang::En).unwrap().is_empty());
+    assert_eq!(saved[&ChannelId(2)], session.record());
+    let restored = GameSession::restore(&question, &saved[&ChannelId(1)]);
+    assert_eq!(restored.hints, 1);
+    assert_eq!(restored.message, Some(MessageId(u64::MAX)));
+    assert_eq!(restored.record().asked, session.record().asked);
+    assert!(restored.active);
+    session.active = false;
+    save_session(&db, ChannelId(1), Lang::Uk, &session.record()).unwrap();
+    let saved = get_sessions(&db, Lang::Uk).unwrap();
+    assert_eq!(saved[&ChannelId(1)], session.record());
+    assert!(!GameSession::restore(&question, &saved[&ChannelId(1)]).active);
+}
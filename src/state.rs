@@ -0,0 +1,180 @@
+use crate::migrations::{migrate, migrate_in_transaction, Migration};
+use sqlite::{Connection, Result};
+use std::path::Path;
+
+/// Opens the state database at `path`, which has everything the bot changes,
+/// and applies migrations it does not have yet, which are returned.
+/// A new database gets scores and channel bindings older versions kept in the `legacy` ones,
+/// such as the dictionaries.
+pub fn open(path: &Path, legacy: &[&Path]) -> Result<(Connection, Vec<&'static Migration>)> {
+    let db = sqlite::open(path)?;
+    if has_table(&db, "main", "scores")? {
+        let applied = migrate(&db)?;
+        return Ok((db, applied));
+    }
+    // the same file may be given twice, e.g. scores kept in the dictionary,
+    // and the database itself has nothing to import
+    let mut sources = vec![];
+    let mut seen = vec![path.canonicalize().ok()];
+    for source in legacy {
+        // attaching creates missing files
+        if source.is_file() && !seen.contains(&source.canonicalize().ok()) {
+            seen.push(source.canonicalize().ok());
+            sources.push(*source);
+        }
+    }
+    // databases can not be attached in a transaction
+    let schemas: Vec<String> = (0..sources.len()).map(|i| format!("legacy{i}")).collect();
+    for (source, schema) in sources.iter().zip(&schemas) {
+        let source = source.to_string_lossy().replace('\'', "''");
+        db.execute(format!("ATTACH DATABASE '{source}' AS {schema}"))?;
+    }
+    // tables are made along with the import, so that a failed one is tried again next time
+    let applied = transaction(&db, "BEGIN", || {
+        let applied = migrate_in_transaction(&db)?;
+        for schema in &schemas {
+            import(&db, schema)?;
+        }
+        Ok(applied)
+    })?;
+    for schema in &schemas {
+        db.execute(format!("DETACH DATABASE {schema}"))?;
+    }
+    Ok((db, applied))
+}
+
+/// Runs `f` in a transaction of the `db` started with `begin`, like "BEGIN IMMEDIATE",
+/// which is committed if it succeeds and rolled back if it or the commit fails
+pub fn transaction<T, E: From<sqlite::Error>>(
+    db: &Connection,
+    begin: &str,
+    f: impl FnOnce() -> std::result::Result<T, E>,
+) -> std::result::Result<T, E> {
+    db.execute(begin)?;
+    let result = f().and_then(|value| {
+        db.execute("COMMIT")?;
+        Ok(value)
+    });
+    if result.is_err() {
+        // the error which made it fail is the one worth reporting
+        let _ = db.execute("ROLLBACK");
+    }
+    result
+}
+
+/// Whether the `schema` of the `db` has the `table`
+fn has_table(db: &Connection, schema: &str, table: &str) -> Result<bool> {
+    let query = format!(
+        "SELECT name FROM {schema}.sqlite_master WHERE type == 'table' AND name == '{table}'"
+    );
+    Ok(db.prepare(query)?.into_iter().next().transpose()?.is_some())
+}
+
+/// Adds scores in the attached `schema` to the ones in the `db` and takes channel bindings it has
+fn import(db: &Connection, schema: &str) -> Result<()> {
+    if has_table(db, schema, "scores")? {
+        // `WHERE true` tells the upsert from a join
+        db.execute(format!(
+            "INSERT INTO scores (user, score) SELECT user, score FROM {schema}.scores WHERE true \
+             ON CONFLICT (user) DO UPDATE SET score = scores.score + excluded.score"
+        ))?;
+    }
+    if has_table(db, schema, "channels")? {
+        db.execute(format!(
+            "INSERT OR IGNORE INTO channels SELECT channel, lang FROM {schema}.channels"
+        ))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+fn temp_db(name: &str) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("gamebot-{}-{name}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn transaction_test() {
+    let db = sqlite::open(":memory:").unwrap();
+    db.execute(
+        "PRAGMA foreign_keys = ON;
+         CREATE TABLE one (id INTEGER PRIMARY KEY);
+         CREATE TABLE two (one INTEGER REFERENCES one (id) DEFERRABLE INITIALLY DEFERRED)",
+    )
+    .unwrap();
+    let result: Result<()> = transaction(&db, "BEGIN", || db.execute("CREATE TABLE three (id)"));
+    assert!(result.is_ok());
+    // the reference is only checked by the commit, which fails
+    let result: Result<()> = transaction(&db, "BEGIN", || db.execute("INSERT INTO two VALUES (1)"));
+    assert!(result.is_err());
+    // and does not leave the transaction open
+    db.execute("BEGIN").unwrap();
+}
+
+#[test]
+fn import_test() {
+    let (uk, en, state) = (temp_db("uk"), temp_db("en"), temp_db("state"));
+    let dictionary = sqlite::open(&uk).unwrap();
+    migrate(&dictionary).unwrap();
+    dictionary
+        .execute("INSERT INTO scores VALUES (1, 10), (2, 5); INSERT INTO channels VALUES (3, 'en')")
+        .unwrap();
+    let dictionary = sqlite::open(&en).unwrap();
+    dictionary
+        .execute("CREATE TABLE scores (user INTEGER PRIMARY KEY UNIQUE, score INTEGER)")
+        .unwrap();
+    dictionary
+        .execute("INSERT INTO scores VALUES (1, 3)")
+        .unwrap();
+    let missing = temp_db("missing");
+
+    let (db, applied) = open(&state, &[&uk, &en, &uk, &missing, &state]).unwrap();
+    assert!(!applied.is_empty());
+    use crate::score::ScoreStore;
+    let score = |db: &Connection, user| db.get_score(user).unwrap().0;
+    assert_eq!(score(&db, 1), 13);
+    assert_eq!(score(&db, 2), 5);
+    assert_eq!(crate::channels::get_bindings(&db).unwrap().len(), 1);
+    assert!(!missing.exists());
+    drop(db);
+
+    // only once
+    let (db, applied) = open(&state, &[&uk, &en]).unwrap();
+    assert!(applied.is_empty());
+    assert_eq!(score(&db, 1), 13);
+    drop(db);
+    for path in [uk, en, state] {
+        std::fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn failed_import_test() {
+    use crate::{migrations::MIGRATIONS, score::ScoreStore};
+    let (uk, state) = (temp_db("broken-uk"), temp_db("broken-state"));
+    let dictionary = sqlite::open(&uk).unwrap();
+    dictionary
+        .execute("CREATE TABLE scores (user INTEGER PRIMARY KEY UNIQUE)")
+        .unwrap();
+    drop(dictionary);
+    assert!(open(&state, &[&uk]).is_err());
+
+    // nothing is left of the failed import, so it is done on the next start
+    std::fs::remove_file(&uk).unwrap();
+    let dictionary = sqlite::open(&uk).unwrap();
+    dictionary
+        .execute(
+            "CREATE TABLE scores (user INTEGER PRIMARY KEY UNIQUE, score INTEGER);
+             INSERT INTO scores VALUES (1, 10)",
+        )
+        .unwrap();
+    drop(dictionary);
+    let (db, applied) = open(&state, &[&uk]).unwrap();
+    assert_eq!(applied.len(), MIGRATIONS.len());
+    assert_eq!(db.get_score(1).unwrap().0, 10);
+    drop(db);
+    for path in [uk, state] {
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,58 @@
+use crate::{
+    config::Config,
+    migrations::migrate,
+    question::{EnQuestion, Question},
+    transport::{ChannelId, MemoryTransport, UserId},
+    Game, Transport,
+};
+use sqlite::Connection;
+use std::time::Duration;
+
+/// The bot in games of tests
+pub const BOT: UserId = UserId(1000);
+
+/// Everything a game in tests is made of, one Ukrainian question unless the test sets others
+pub struct Fixture {
+    pub db: Connection,
+    pub config: Config,
+    pub uk: Vec<Question>,
+    pub en: Vec<EnQuestion>,
+}
+
+impl Default for Fixture {
+    fn default() -> Self {
+        let db = sqlite::open(":memory:").unwrap();
+        migrate(&db).unwrap();
+        let mut config = Config::default();
+        // channels of `transport` get their language by name
+        config.match_channel_names = true;
+        Fixture {
+            db,
+            config,
+            uk: vec![Question::new("Свійська тварина".into(), "кіт".into(), 3)],
+            en: vec![],
+        }
+    }
+}
+
+impl Fixture {
+    /// Game of the `BOT` with the fixture, new ones carry on what was saved by the others
+    pub fn game(&self) -> Game<'_> {
+        Game::new(&self.config, BOT, &self.db, &self.uk, &self.en).unwrap()
+    }
+}
+
+/// Transport of the `BOT` with Ukrainian channels 1 and 2
+pub fn transport() -> MemoryTransport {
+    let mut transport = MemoryTransport::new(BOT);
+    transport.add_channel(ChannelId(1), "гра-uk");
+    transport.add_channel(ChannelId(2), "ще-uk");
+    transport
+}
+
+/// Handles all events queued in the `transport`
+pub fn run(game: &mut Game, transport: &mut MemoryTransport) {
+    while let Ok(Some(event)) = transport.recv_event(Duration::ZERO) {
+        game.handle_event(transport, event).unwrap();
+    }
+}
@@ -0,0 +1,246 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, VecDeque},
+    fmt::Display,
+    time::Duration,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ChannelId(pub u64);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MessageId(pub u64);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct UserId(pub u64);
+
+/// Channel details the game needs to pick a language
+#[derive(Clone, Debug)]
+pub struct ChannelInfo {
+    pub id: ChannelId,
+    // `None` for private and group channels
+    pub name: Option<String>,
+}
+
+/// Message posted to a channel
+#[derive(Clone, Debug)]
+pub struct ChatMessage {
+    pub id: MessageId,
+    pub channel: ChannelId,
+    pub author: UserId,
+    pub bot: bool,
+    pub content: String,
+}
+
+/// Reaction added to a message
+#[derive(Clone, Debug)]
+pub struct ChatReaction {
+    pub channel: ChannelId,
+    pub message: MessageId,
+    pub user: UserId,
+    pub emoji: String,
+}
+
+#[derive(Clone, Debug)]
+pub enum ChatEvent {
+    Message(ChatMessage),
+    Reaction(ChatReaction),
+    // Any other gateway event the game is not interested in
+    Other,
+}
+
+/// Chat service the game is played over
+pub trait Transport {
+    type Error: std::error::Error + 'static;
+
+    /// Posts `text` to the `channel` and returns id of the new message
+    fn send_text(&self, channel: ChannelId, text: &str) -> Result<MessageId, Self::Error>;
+    /// Reacts to the `message` with unicode `emoji`
+    fn react(&self, channel: ChannelId, message: MessageId, emoji: &str)
+        -> Result<(), Self::Error>;
+    fn channel(&self, channel: ChannelId) -> Result<ChannelInfo, Self::Error>;
+    /// Author of already posted `message`
+    fn message_author(&self, channel: ChannelId, message: MessageId)
+        -> Result<UserId, Self::Error>;
+    /// Blocks until next event arrives or the `timeout` passes, `None` on timeout
+    fn recv_event(&mut self, timeout: Duration) -> Result<Option<ChatEvent>, Self::Error>;
+}
+
+/// In-memory transport which records everything sent through it
+pub struct MemoryTransport {
+    pub user: UserId,
+    pub channels: HashMap<ChannelId, ChannelInfo>,
+    pub events: VecDeque<ChatEvent>,
+    pub sent: RefCell<Vec<ChatMessage>>,
+    pub reactions: RefCell<Vec<ChatReaction>>,
+    authors: RefCell<HashMap<MessageId, UserId>>,
+    next_id: Cell<u64>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum MemoryError {
+    UnknownChannel(ChannelId),
+    UnknownMessage(MessageId),
+    // all queued events were received
+    Drained,
+}
+
+impl Display for MemoryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MemoryError::UnknownChannel(channel) => write!(f, "unknown channel {}", channel.0),
+            MemoryError::UnknownMessage(message) => write!(f, "unknown message {}", message.0),
+            MemoryError::Drained => write!(f, "no more events"),
+        }
+    }
+}
+
+impl std::error::Error for MemoryError {}
+
+impl MemoryTransport {
+    /// Transport which posts messages as `user`
+    pub fn new(user: UserId) -> Self {
+        MemoryTransport {
+            user,
+            channels: HashMap::new(),
+            events: VecDeque::new(),
+            sent: RefCell::new(Vec::new()),
+            reactions: RefCell::new(Vec::new()),
+            authors: RefCell::new(HashMap::new()),
+            next_id: Cell::new(1),
+        }
+    }
+
+    pub fn add_channel(&mut self, id: ChannelId, name: &str) {
+        self.channels.insert(
+            id,
+            ChannelInfo {
+                id,
+                name: Some(name.to_string()),
+            },
+        );
+    }
+
+    /// Queues message from `author` to be received with `recv_event`
+    pub fn push_message(&mut self, channel: ChannelId, author: UserId, content: &str) -> MessageId {
+        let id = self.new_id();
+        self.authors.borrow_mut().insert(id, author);
+        self.events.push_back(ChatEvent::Message(ChatMessage {
+            id,
+            channel,
+            author,
+            bot: false,
+            content: content.to_string(),
+        }));
+        id
+    }
+
+    /// Queues reaction from `user` to be received with `recv_event`
+    pub fn push_reaction(
+        &mut self,
+        channel: ChannelId,
+        message: MessageId,
+        user: UserId,
+        emoji: &str,
+    ) {
+        self.events.push_back(ChatEvent::Reaction(ChatReaction {
+            channel,
+            message,
+            user,
+            emoji: emoji.to_string(),
+        }));
+    }
+
+    /// Messages sent to the `channel` so far
+    pub fn sent(&self, channel: ChannelId) -> Vec<ChatMessage> {
+        self.sent
+            .borrow()
+            .iter()
+            .filter(|m| m.channel == channel)
+            .cloned()
+            .collect()
+    }
+
+    /// Texts sent to the `channel` so far
+    pub fn texts(&self, channel: ChannelId) -> Vec<String> {
+        self.sent(channel).into_iter().map(|m| m.content).collect()
+    }
+
+    /// Emojis put under the `message` so far
+    pub fn reactions_to(&self, message: MessageId) -> Vec<String> {
+        self.reactions
+            .borrow()
+            .iter()
+            .filter(|r| r.message == message)
+            .map(|r| r.emoji.clone())
+            .collect()
+    }
+
+    fn new_id(&self) -> MessageId {
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+        MessageId(id)
+    }
+}
+
+impl Transport for MemoryTransport {
+    type Error = MemoryError;
+
+    fn send_text(&self, channel: ChannelId, text: &str) -> Result<MessageId, Self::Error> {
+        if !self.channels.contains_key(&channel) {
+            return Err(MemoryError::UnknownChannel(channel));
+        }
+        let id = self.new_id();
+        self.authors.borrow_mut().insert(id, self.user);
+        self.sent.borrow_mut().push(ChatMessage {
+            id,
+            channel,
+            author: self.user,
+            bot: true,
+            content: text.to_string(),
+        });
+        Ok(id)
+    }
+
+    fn react(
+        &self,
+        channel: ChannelId,
+        message: MessageId,
+        emoji: &str,
+    ) -> Result<(), Self::Error> {
+        self.reactions.borrow_mut().push(ChatReaction {
+            channel,
+            message,
+            user: self.user,
+            emoji: emoji.to_string(),
+        });
+        Ok(())
+    }
+
+    fn channel(&self, channel: ChannelId) -> Result<ChannelInfo, Self::Error> {
+        self.channels
+            .get(&channel)
+            .cloned()
+            .ok_or(MemoryError::UnknownChannel(channel))
+    }
+
+    fn message_author(
+        &self,
+        _channel: ChannelId,
+        message: MessageId,
+    ) -> Result<UserId, Self::Error> {
+        self.authors
+            .borrow()
+            .get(&message)
+            .copied()
+            .ok_or(MemoryError::UnknownMessage(message))
+    }
+
+    /// Never waits, running out of queued events is an error
+    fn recv_event(&mut self, _timeout: Duration) -> Result<Option<ChatEvent>, Self::Error> {
+        self.events
+            .pop_front()
+            .map(Some)
+            .ok_or(MemoryError::Drained)
+    }
+}
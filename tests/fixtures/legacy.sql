@@ -0,0 +1,6 @@
+-- Dictionary database of the bot before migrations, which kept the scores
+-- in a table made at startup and had no schema version
+CREATE TABLE wlist (id_syn INTEGER, word TEXT, interpretation TEXT);
+CREATE TABLE scores (user INTEGER PRIMARY KEY UNIQUE, score INTEGER);
+INSERT INTO wlist VALUES (7, 'кіт', 'Свійська тварина'), (7, 'котик', 'Кіт');
+INSERT INTO scores VALUES (241977946434076672, 42), (7, 3);
@@ -0,0 +1,465 @@
+extern crate gamebot;
+
+use gamebot::{
+    channels::{get_bindings, GameState},
+    config::Config,
+    error::Error,
+    question::{EnQuestion, IsQuestion, Lang, Question},
+    score::{MemoryScores, ScoreStore},
+    session::get_sessions,
+    testing::{run, transport, Fixture, BOT},
+    transport::{ChannelId, MemoryError, UserId},
+    Action, Transport,
+};
+use std::time::{Duration, SystemTime};
+
+#[test]
+fn answer_flow_test() {
+    let fixture = Fixture::default();
+    let mut game = fixture.game();
+    let mut transport = transport();
+    // the first message only brings the question, answers come after it
+    let early = transport.push_message(ChannelId(1), UserId(7), "кіт");
+    let wrong = transport.push_message(ChannelId(1), UserId(7), "пес");
+    transport.push_message(ChannelId(1), UserId(7), "Кіт");
+    run(&mut game, &mut transport);
+    assert!(transport.reactions_to(early).is_empty());
+    assert_eq!(transport.reactions_to(wrong), vec!["➖"]);
+    let texts = transport.texts(ChannelId(1));
+    assert_eq!(texts[0], fixture.uk[0].to_string());
+    assert_eq!(texts[1], "Вірно <@7>. Відповідь кіт. Загальний рейтинг: 3");
+    assert_eq!(texts[2], fixture.uk[0].to_string());
+    assert_eq!(fixture.db.get_score(7).unwrap(), (3, 1, 1));
+}
+
+#[test]
+fn channels_are_independent_test() {
+    let fixture = Fixture::default();
+    let mut game = fixture.game();
+    let mut transport = transport();
+    transport.push_message(ChannelId(1), UserId(7), "!п");
+    transport.push_message(ChannelId(2), UserId(8), "!рейтинг");
+    transport.push_message(ChannelId(1), UserId(7), "кіт");
+    run(&mut game, &mut transport);
+    assert_eq!(transport.texts(ChannelId(1)).len(), 3);
+    assert_eq!(transport.texts(ChannelId(2)).len(), 1);
+}
+
+#[test]
+fn pure_engine_test() {
+    let fixture = Fixture::default();
+    let mut game = fixture.game();
+    let transport = transport();
+    let channel = transport.channel(ChannelId(1)).unwrap();
+    let message = gamebot::transport::ChatMessage {
+        id: gamebot::transport::MessageId(10),
+        channel: ChannelId(1),
+        author: UserId(7),
+        bot: false,
+        content: "!п".into(),
+    };
+    assert_eq!(
+        game.handle_message(&message, &channel).unwrap(),
+        vec![Action::Ask {
+            channel: ChannelId(1),
+            text: fixture.uk[0].to_string()
+        }]
+    );
+}
+
+#[test]
+fn own_messages_ignored_test() {
+    let fixture = Fixture::default();
+    let mut game = fixture.game();
+    let mut transport = transport();
+    transport.push_message(ChannelId(1), BOT, "Відповідь кіт");
+    run(&mut game, &mut transport);
+    assert!(transport.texts(ChannelId(1)).is_empty());
+    assert_eq!(fixture.db.get_score(BOT.0).unwrap(), (0, 0, 0));
+}
+
+#[test]
+fn bind_test() {
+    let mut fixture = Fixture::default();
+    fixture.config.admins = vec![7];
+    let mut game = fixture.game();
+    let mut transport = transport();
+    transport.add_channel(ChannelId(3), "quiz");
+    let denied = transport.push_message(ChannelId(3), UserId(8), "!bind uk");
+    let unknown = transport.push_message(ChannelId(3), UserId(7), "!bind de");
+    let bound = transport.push_message(ChannelId(3), UserId(7), "!bind uk");
+    transport.push_message(ChannelId(3), UserId(8), "!п");
+    transport.push_message(ChannelId(3), UserId(8), "кіт");
+    run(&mut game, &mut transport);
+    assert_eq!(transport.texts(ChannelId(3))[0], fixture.uk[0].to_string());
+    assert_eq!(transport.reactions_to(denied), vec!["🛑"]);
+    assert_eq!(transport.reactions_to(unknown), vec!["❌"]);
+    assert_eq!(transport.reactions_to(bound), vec!["✅"]);
+    assert_eq!(get_bindings(&fixture.db).unwrap()[&ChannelId(3)], Lang::Uk);
+    assert_eq!(fixture.db.get_score(8).unwrap(), (3, 1, 1));
+
+    transport.push_message(ChannelId(3), UserId(7), "!unbind");
+    transport.push_message(ChannelId(3), UserId(8), "кіт");
+    run(&mut game, &mut transport);
+    assert!(get_bindings(&fixture.db).unwrap().is_empty());
+    assert_eq!(fixture.db.get_score(8).unwrap(), (3, 1, 1));
+
+    // binding again starts over with the question posted first
+    let texts = transport.texts(ChannelId(3)).len();
+    transport.push_message(ChannelId(3), UserId(7), "!bind uk");
+    let first = transport.push_message(ChannelId(3), UserId(8), "кіт");
+    run(&mut game, &mut transport);
+    assert!(transport.reactions_to(first).is_empty());
+    assert_eq!(
+        transport.texts(ChannelId(3))[texts],
+        fixture.uk[0].to_string()
+    );
+    assert_eq!(fixture.db.get_score(8).unwrap(), (3, 1, 1));
+}
+
+#[test]
+fn help_test() {
+    let mut fixture = Fixture::default();
+    fixture.config.prefix = ".".into();
+    fixture.config.admins = vec![7];
+    let mut game = fixture.game();
+    let mut transport = transport();
+    transport.push_message(ChannelId(1), UserId(8), ".?");
+    transport.push_message(ChannelId(2), UserId(7), ".help");
+    transport.push_message(ChannelId(1), UserId(8), ".п");
+    let ignored = transport.push_message(ChannelId(1), UserId(8), "!далі");
+    run(&mut game, &mut transport);
+    let help = &transport.texts(ChannelId(1))[0];
+    assert!(help.contains("**.next** | **.далі** | **.відповідь** - "));
+    assert!(!help.contains(".bind"));
+    assert!(transport.texts(ChannelId(2))[0].contains("**.bind** - "));
+    assert_eq!(transport.reactions_to(ignored), vec!["➖"]);
+}
+
+#[test]
+fn name_matching_test() {
+    let mut fixture = Fixture::default();
+    fixture.config.match_channel_names = Config::default().match_channel_names;
+    let game = fixture.game();
+    let mut transport = transport();
+    let channel = transport.channel(ChannelId(1)).unwrap();
+    assert_eq!(game.channel_lang(&channel), Lang::Uknown);
+
+    fixture.config.match_channel_names = true;
+    let game = fixture.game();
+    assert_eq!(game.channel_lang(&channel), Lang::Uk);
+    transport.add_channel(ChannelId(3), "general");
+    transport.add_channel(ChannelId(4), "events");
+    transport.add_channel(ChannelId(5), "words_en");
+    let lang = |id| game.channel_lang(&transport.channel(ChannelId(id)).unwrap());
+    assert_eq!(lang(3), Lang::Uknown);
+    assert_eq!(lang(4), Lang::Uknown);
+    assert_eq!(lang(5), Lang::En);
+}
+
+#[test]
+fn failed_event_test() {
+    let mut fixture = Fixture::default();
+    fixture.uk.clear();
+    let mut game = fixture.game();
+    let mut transport = transport();
+    let message = transport.push_message(ChannelId(1), UserId(7), "!п");
+    let event = transport.recv_event(Duration::ZERO).unwrap().unwrap();
+    assert!(matches!(
+        game.handle_event(&transport, event),
+        Err(Error::NoQuestions(Lang::Uk))
+    ));
+    assert_eq!(transport.reactions_to(message), vec!["⚠️"]);
+
+    // the channel disappeared before the event was handled
+    transport.push_message(ChannelId(3), UserId(7), "!п");
+    let event = transport.recv_event(Duration::ZERO).unwrap().unwrap();
+    match game.handle_event(&transport, event) {
+        Err(Error::Transport(e)) => assert_eq!(
+            e.downcast_ref::<MemoryError>(),
+            Some(&MemoryError::UnknownChannel(ChannelId(3)))
+        ),
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn english_test() {
+    let fixture = Fixture {
+        en: vec![EnQuestion {
+            id: 1,
+            question: "Domestic animal".into(),
+            answer: "cat".into(),
+            score: 2,
+        }],
+        ..Fixture::default()
+    };
+    let mut game = fixture.game();
+    let mut transport = transport();
+    transport.add_channel(ChannelId(3), "game-en");
+    transport.push_message(ChannelId(3), UserId(7), "!q");
+    transport.push_message(ChannelId(3), UserId(7), "a Cat!");
+    transport.push_message(ChannelId(3), UserId(7), "!score");
+    run(&mut game, &mut transport);
+    assert_eq!(
+        transport.texts(ChannelId(3)),
+        vec![
+            fixture.en[0].to_string(),
+            "Correct <@7>. Answer is **cat**. Your total score: 2".to_string(),
+            fixture.en[0].to_string(),
+            "<@7> have 2 point and is 1 out of 1".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn hinted_answer_test() {
+    let mut fixture = Fixture::default();
+    fixture.uk[0].score = 10;
+    fixture.config.min_pause = 0;
+    fixture.config.hint_cost = "3".parse().unwrap();
+    let mut game = fixture.game();
+    let mut transport = transport();
+    transport.push_message(ChannelId(1), UserId(7), "!п");
+    transport.push_message(ChannelId(1), UserId(7), "!хінт");
+    transport.push_message(ChannelId(1), UserId(7), "!хінт");
+    transport.push_message(ChannelId(1), UserId(7), "кіт");
+    run(&mut game, &mut transport);
+    let texts = transport.texts(ChannelId(1));
+    assert_eq!(texts[0], fixture.uk[0].to_string());
+    assert_eq!(texts[1], "◾◾◾ [+7]");
+    assert_eq!(texts[2], "к◾т [+4]");
+    assert_eq!(texts[3], "Вірно <@7>. Відповідь кіт. Загальний рейтинг: 4");
+    assert_eq!(fixture.db.get_score(7).unwrap(), (4, 1, 1));
+}
+
+#[test]
+fn stressed_answer_test() {
+    let fixture = Fixture {
+        uk: vec![Question::new("Число".into(), "сі\u{301}м".into(), 3)],
+        ..Fixture::default()
+    };
+    let mut game = fixture.game();
+    let mut transport = transport();
+    transport.push_message(ChannelId(1), UserId(7), "!п");
+    transport.push_message(ChannelId(1), UserId(7), "СІМ");
+    run(&mut game, &mut transport);
+    let texts = transport.texts(ChannelId(1));
+    assert_eq!(texts[0], "**Число** (3 літер) [+3]");
+    assert_eq!(
+        texts[1],
+        "Вірно <@7>. Відповідь сі\u{301}м. Загальний рейтинг: 3"
+    );
+}
+
+#[test]
+fn typo_answer_test() {
+    let mut fixture = Fixture {
+        uk: vec![Question::new("Фрукт".into(), "яблуко".into(), 10)],
+        ..Fixture::default()
+    };
+    fixture.config = Config::parse(
+        "match_channel_names = true\ntypos = \"almost\"\n[channel_typos]\n2 = \"accept\"",
+    )
+    .unwrap();
+    fixture.config.uk_dictionary = file!().into();
+    fixture.config.en_dictionary = file!().into();
+    fixture.config.validate().unwrap();
+    let mut game = fixture.game();
+    let mut transport = transport();
+    transport.push_message(ChannelId(1), UserId(7), "!п");
+    transport.push_message(ChannelId(2), UserId(8), "!п");
+    let almost = transport.push_message(ChannelId(1), UserId(7), "ябулко");
+    let wrong = transport.push_message(ChannelId(1), UserId(7), "ябко");
+    transport.push_message(ChannelId(2), UserId(8), "ябулко");
+    run(&mut game, &mut transport);
+    assert_eq!(transport.reactions_to(almost), ["🤏"]);
+    assert_eq!(transport.reactions_to(wrong), ["➖"]);
+    assert_eq!(transport.texts(ChannelId(1)), [fixture.uk[0].to_string()]);
+    // accepted as if after a hint, 20% off
+    assert_eq!(
+        transport.texts(ChannelId(2))[1],
+        "Вірно <@8>. Відповідь яблуко. Загальний рейтинг: 8"
+    );
+}
+
+#[test]
+fn guess_detection_test() {
+    let mut fixture = Fixture::default();
+    fixture.config.guesses = "words".parse().unwrap();
+    let mut game = fixture.game();
+    let mut transport = transport();
+    let chat = transport.push_message(ChannelId(1), UserId(7), "всім привіт");
+    let wrong = transport.push_message(ChannelId(1), UserId(7), "пес");
+    run(&mut game, &mut transport);
+    assert!(transport.reactions_to(chat).is_empty());
+    assert_eq!(transport.reactions_to(wrong), ["➖"]);
+
+    fixture.config.react_wrong = false;
+    let mut game = fixture.game();
+    let wrong = transport.push_message(ChannelId(1), UserId(7), "пес");
+    transport.push_message(ChannelId(1), UserId(7), "мабуть це кіт");
+    run(&mut game, &mut transport);
+    assert!(transport.reactions_to(wrong).is_empty());
+    assert_eq!(fixture.db.get_score(7).unwrap(), (3, 1, 1));
+}
+
+#[test]
+fn start_stop_test() {
+    let mut fixture = Fixture::default();
+    fixture.config.admins = vec![7];
+    fixture.config.min_pause = 0;
+    let mut game = fixture.game();
+    let mut transport = transport();
+    transport.push_message(ChannelId(1), UserId(8), "!п");
+    run(&mut game, &mut transport);
+    let question = transport.sent(ChannelId(1))[0].id;
+    let denied = transport.push_message(ChannelId(1), UserId(8), "!пауза");
+    let paused = transport.push_message(ChannelId(1), UserId(7), "!пауза");
+    let hint = transport.push_message(ChannelId(1), UserId(8), "!хінт");
+    transport.push_reaction(ChannelId(1), question, UserId(8), "❓");
+    transport.push_message(ChannelId(1), UserId(8), "кіт");
+    run(&mut game, &mut transport);
+    assert_eq!(transport.reactions_to(denied), ["🛑"]);
+    assert_eq!(transport.reactions_to(paused), ["✅"]);
+    assert_eq!(transport.reactions_to(hint), ["⏸️"]);
+    // no hint for the reaction either
+    assert_eq!(transport.texts(ChannelId(1)).len(), 1);
+    assert_eq!(fixture.db.get_score(8).unwrap(), (0, 0, 0));
+    assert!(game
+        .timers(SystemTime::now() + Duration::from_secs(600))
+        .unwrap()
+        .is_empty());
+
+    // the state outlives the game
+    let mut game = fixture.game();
+    assert_eq!(game.state(ChannelId(1)), GameState::Paused);
+    transport.push_message(ChannelId(1), UserId(7), "!resume");
+    transport.push_message(ChannelId(1), UserId(8), "кіт");
+    run(&mut game, &mut transport);
+    assert_eq!(fixture.db.get_score(8).unwrap(), (3, 1, 1));
+    let texts = transport.texts(ChannelId(1));
+    assert_eq!(texts[0], fixture.uk[0].to_string());
+
+    transport.push_message(ChannelId(1), UserId(7), "!stop");
+    transport.push_message(ChannelId(1), UserId(8), "кіт");
+    transport.push_message(ChannelId(1), UserId(8), "!рейтинг");
+    transport.push_message(ChannelId(1), UserId(8), "!help");
+    run(&mut game, &mut transport);
+    // help and scores are there without a question
+    assert_eq!(transport.texts(ChannelId(1)).len(), texts.len() + 2);
+    assert!(get_sessions(&fixture.db, Lang::Uk).unwrap().is_empty());
+    transport.push_message(ChannelId(1), UserId(7), "!start");
+    run(&mut game, &mut transport);
+    assert_eq!(game.state(ChannelId(1)), GameState::Running);
+    assert_eq!(fixture.db.get_score(8).unwrap(), (3, 1, 1));
+    assert_eq!(transport.texts(ChannelId(1)).len(), texts.len() + 3);
+}
+
+#[test]
+fn deck_test() {
+    let fixture = Fixture {
+        uk: vec![
+            Question::new("Свійська тварина".into(), "кіт".into(), 1),
+            Question::new("Друг людини".into(), "пес".into(), 1),
+            Question::new("Водойма".into(), "море".into(), 1),
+            Question::new("Дерево".into(), "дуб".into(), 1),
+        ],
+        ..Fixture::default()
+    };
+    let mut transport = transport();
+    let mut game = fixture.game();
+    transport.push_message(ChannelId(1), UserId(7), "!п");
+    run(&mut game, &mut transport);
+    for _ in 0..2 {
+        let question = transport.texts(ChannelId(1)).pop().unwrap();
+        let answer = fixture
+            .uk
+            .iter()
+            .find(|q| q.to_string() == question)
+            .unwrap();
+        transport.push_message(ChannelId(1), UserId(7), answer.get_answer());
+        run(&mut game, &mut transport);
+    }
+    // a new game carries on with the same deck
+    let mut game = fixture.game();
+    transport.push_message(ChannelId(1), UserId(7), "!п");
+    run(&mut game, &mut transport);
+    let mut asked: Vec<String> = transport
+        .texts(ChannelId(1))
+        .into_iter()
+        .filter(|text| fixture.uk.iter().any(|q| q.to_string() == *text))
+        .collect();
+    assert_eq!(asked.len(), 4);
+    asked.sort();
+    asked.dedup();
+    assert_eq!(asked.len(), fixture.uk.len());
+}
+
+#[test]
+fn restore_test() {
+    let mut fixture = Fixture {
+        uk: vec![
+            Question::new("Свійська тварина".into(), "кіт".into(), 10),
+            Question::new("Друг людини".into(), "пес".into(), 10),
+        ],
+        ..Fixture::default()
+    };
+    fixture.uk[1].id = 1;
+    fixture.config.min_pause = 0;
+    let mut transport = transport();
+    let mut game = fixture.game();
+    transport.push_message(ChannelId(1), UserId(7), "!п");
+    transport.push_message(ChannelId(1), UserId(7), "!хінт");
+    run(&mut game, &mut transport);
+
+    // the hinted question is carried on
+    let mut game = fixture.game();
+    game.restore(&transport).unwrap();
+    assert_eq!(transport.texts(ChannelId(1)).len(), 2);
+    transport.push_message(ChannelId(1), UserId(7), "кіт пес");
+    run(&mut game, &mut transport);
+    assert_eq!(fixture.db.get_score(7).unwrap(), (8, 1, 1));
+
+    // the question is gone from the dictionary
+    let asked = transport.texts(ChannelId(1)).pop().unwrap();
+    fixture.uk.retain(|q| q.to_string() != asked);
+    let mut game = fixture.game();
+    game.restore(&transport).unwrap();
+    let texts = transport.texts(ChannelId(1));
+    assert_eq!(texts[texts.len() - 2], "Бот повернувся, поточне питання:");
+    assert_eq!(texts[texts.len() - 1], fixture.uk[0].to_string());
+
+    // nobody played after the answer was revealed, so the channel stays quiet
+    let later = |secs| SystemTime::now() + Duration::from_secs(secs);
+    for _ in 0..5 {
+        let actions = game.timers(later(600)).unwrap();
+        game.apply(&transport, actions).unwrap();
+    }
+    let texts = transport.texts(ChannelId(1));
+    assert_eq!(texts[texts.len() - 2], fixture.uk[0].get_answer());
+    // sessions are saved with the next event
+    transport.push_message(ChannelId(2), UserId(7), "!рейтинг");
+    run(&mut game, &mut transport);
+    let mut game = fixture.game();
+    game.restore(&transport).unwrap();
+    assert!(game.timers(later(600)).unwrap().is_empty());
+}
+
+#[test]
+fn memory_scores_test() {
+    let fixture = Fixture::default();
+    let scores = MemoryScores::default();
+    let mut game = fixture.game().with_scores(&scores);
+    let mut transport = transport();
+    transport.push_message(ChannelId(1), UserId(7), "!п");
+    transport.push_message(ChannelId(1), UserId(7), "кіт");
+    transport.push_message(ChannelId(1), UserId(7), "!рейтинг");
+    run(&mut game, &mut transport);
+    assert_eq!(scores.get_score(7).unwrap(), (3, 1, 1));
+    assert_eq!(fixture.db.get_score(7).unwrap(), (0, 0, 0));
+    assert_eq!(
+        transport.texts(ChannelId(1))[3],
+        "<@7> має 3 очок і є 1 зі 1"
+    );
+}